@@ -0,0 +1,882 @@
+//! Automatic Level Control (ALC) for the NAU88C22
+//!
+//! The ALC adjusts the input PGA gain to keep the ADC output near a target
+//! level. In *normal* mode it behaves like a slow automatic gain control; in
+//! *limiter* mode it uses faster time constants and only reduces the gain
+//! when the signal goes above the target.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use core::time::Duration;
+//! use nau88c22::alc::{AlcChannels, AlcConfig};
+//! let config = AlcConfig::new()
+//!     .target_dbfs(-12.0)
+//!     .gain_limits_db(-6.0, 23.25)
+//!     .hold(Duration::from_millis(16))
+//!     .decay(Duration::from_millis(32));
+//! codec.configure_alc(AlcChannels::Both, &config)?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::time::Duration;
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+    util,
+    volume::{Channel, Decibels, GainRange, VolumeStage},
+    Codec, Error, Interface,
+};
+
+/// Which channels the ALC controls ([`registers::ALCControl1::alcen`])
+///
+/// [`registers::ALCControl1::alcen`]: crate::registers::ALCControl1::alcen
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlcChannels {
+    /// The ALC is disabled (default)
+    Off,
+    /// Only the left channel
+    Left,
+    /// Only the right channel
+    Right,
+    /// Both channels
+    Both,
+}
+
+impl AlcChannels {
+    /// Does the ALC control this channel?
+    pub const fn includes(self, channel: Channel) -> bool {
+        matches!(
+            (self, channel),
+            (AlcChannels::Both, _)
+                | (AlcChannels::Left, Channel::Left)
+                | (AlcChannels::Right, Channel::Right)
+        )
+    }
+}
+
+impl From<u8> for AlcChannels {
+    fn from(value: u8) -> AlcChannels {
+        match value & 0x03 {
+            1 => AlcChannels::Right,
+            2 => AlcChannels::Left,
+            3 => AlcChannels::Both,
+            _ => AlcChannels::Off,
+        }
+    }
+}
+
+impl From<AlcChannels> for u8 {
+    fn from(value: AlcChannels) -> u8 {
+        match value {
+            AlcChannels::Off => 0,
+            AlcChannels::Right => 1,
+            AlcChannels::Left => 2,
+            AlcChannels::Both => 3,
+        }
+    }
+}
+
+/// How the ALC behaves ([`registers::ALCControl3::alcm`])
+///
+/// [`registers::ALCControl3::alcm`]: crate::registers::ALCControl3::alcm
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlcMode {
+    /// Automatic gain control (default)
+    Normal,
+    /// Peak limiting, with faster attack and decay
+    Limiter,
+}
+
+/// Describes the ALC behaviour we want
+///
+/// The defaults match the CODEC's reset values: a -6 dBFS target, gain
+/// limits of -12 dB and +35.25 dB, no hold time, a 500 µs attack and a 4 ms
+/// decay in normal mode.
+///
+/// The attack and decay times are per 0.75 dB step of PGA gain, so a 6 dB
+/// change in level takes eight times as long to correct.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlcConfig {
+    mode: AlcMode,
+    target_dbfs: f32,
+    min_gain_db: f32,
+    max_gain_db: f32,
+    hold: Duration,
+    attack: Duration,
+    decay: Duration,
+}
+
+impl AlcConfig {
+    /// The target levels in the default table ([`registers::ALCControl2::alcsl`])
+    ///
+    /// Code `15` gives the same level as `14`, so is not used.
+    ///
+    /// [`registers::ALCControl2::alcsl`]: crate::registers::ALCControl2::alcsl
+    pub const TARGET_RANGE: GainRange = GainRange {
+        min_code: 0,
+        max_code: 14,
+        min_db: -22.5,
+        step_db: 1.5,
+    };
+
+    /// The target levels in the optional table
+    /// ([`registers::AlcEnhancements1::alctblsel`])
+    ///
+    /// [`registers::AlcEnhancements1::alctblsel`]: crate::registers::AlcEnhancements1::alctblsel
+    pub const LOW_TARGET_RANGE: GainRange = GainRange {
+        min_code: 0,
+        max_code: 15,
+        min_db: -28.5,
+        step_db: 1.5,
+    };
+
+    /// The maximum PGA gain limits ([`registers::ALCControl1::alcmxgain`])
+    ///
+    /// [`registers::ALCControl1::alcmxgain`]: crate::registers::ALCControl1::alcmxgain
+    pub const MAX_GAIN_RANGE: GainRange = GainRange {
+        min_code: 0,
+        max_code: 7,
+        min_db: -6.75,
+        step_db: 6.0,
+    };
+
+    /// The minimum PGA gain limits ([`registers::ALCControl1::alcmngain`])
+    ///
+    /// [`registers::ALCControl1::alcmngain`]: crate::registers::ALCControl1::alcmngain
+    pub const MIN_GAIN_RANGE: GainRange = GainRange {
+        min_code: 0,
+        max_code: 7,
+        min_db: -12.0,
+        step_db: 6.0,
+    };
+
+    /// The hold times, in µs, indexed by
+    /// [`registers::ALCControl2::alcht`] value
+    ///
+    /// [`registers::ALCControl2::alcht`]: crate::registers::ALCControl2::alcht
+    pub const HOLD_TIMES_US: [u32; 11] = [
+        0, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000, 128_000, 256_000, 512_000, 1_000_000,
+    ];
+
+    /// The normal mode attack times, in µs, indexed by
+    /// [`registers::ALCControl3::alcatk`] value
+    ///
+    /// [`registers::ALCControl3::alcatk`]: crate::registers::ALCControl3::alcatk
+    pub const NORMAL_ATTACK_TIMES_US: [u32; 11] = [
+        125, 250, 500, 1_000, 2_000, 4_000, 8_000, 16_000, 26_500, 53_000, 128_000,
+    ];
+
+    /// The limiter mode attack times, in µs, indexed by
+    /// [`registers::ALCControl3::alcatk`] value
+    ///
+    /// [`registers::ALCControl3::alcatk`]: crate::registers::ALCControl3::alcatk
+    pub const LIMITER_ATTACK_TIMES_US: [u32; 11] = [
+        31, 62, 124, 248, 496, 992, 1_984, 3_968, 7_950, 15_900, 31_700,
+    ];
+
+    /// The normal mode decay times, in µs, indexed by
+    /// [`registers::ALCControl3::alcdcy`] value
+    ///
+    /// [`registers::ALCControl3::alcdcy`]: crate::registers::ALCControl3::alcdcy
+    pub const NORMAL_DECAY_TIMES_US: [u32; 11] = [
+        500, 1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000, 128_000, 256_000, 512_000,
+    ];
+
+    /// The limiter mode decay times, in µs, indexed by
+    /// [`registers::ALCControl3::alcdcy`] value
+    ///
+    /// [`registers::ALCControl3::alcdcy`]: crate::registers::ALCControl3::alcdcy
+    pub const LIMITER_DECAY_TIMES_US: [u32; 11] = [
+        125, 250, 500, 1_000, 2_000, 4_000, 8_000, 16_000, 32_000, 64_000, 128_000,
+    ];
+
+    /// Describe the CODEC's default ALC settings
+    ///
+    /// ```
+    /// use nau88c22::alc::AlcConfig;
+    /// use nau88c22::registers::{ALCControl1, ALCControl2, ALCControl3, AlcEnhancements1};
+    /// let settings = AlcConfig::new().compute().unwrap();
+    /// let (control1, control2) = (ALCControl1::default(), ALCControl2::default());
+    /// let (control3, enhancements1) = (ALCControl3::default(), AlcEnhancements1::default());
+    /// assert_eq!(settings.alcmxgain, control1.alcmxgain());
+    /// assert_eq!(settings.alcmngain, control1.alcmngain());
+    /// assert_eq!(settings.alcht, control2.alcht());
+    /// assert_eq!(settings.alcsl, control2.alcsl());
+    /// assert_eq!(settings.alcm, control3.alcm());
+    /// assert_eq!(settings.alcdcy, control3.alcdcy());
+    /// assert_eq!(settings.alcatk, control3.alcatk());
+    /// assert_eq!(settings.alctblsel, enhancements1.alctblsel());
+    /// ```
+    pub const fn new() -> AlcConfig {
+        AlcConfig {
+            mode: AlcMode::Normal,
+            target_dbfs: -6.0,
+            min_gain_db: -12.0,
+            max_gain_db: 35.25,
+            hold: Duration::ZERO,
+            attack: Duration::from_micros(500),
+            decay: Duration::from_millis(4),
+        }
+    }
+
+    /// Select normal or limiter mode.
+    ///
+    /// This changes which attack and decay times are on offer.
+    pub const fn mode(self, mode: AlcMode) -> AlcConfig {
+        AlcConfig { mode, ..self }
+    }
+
+    /// Set the target level at the ADC output, from -28.5 dBFS to -1.5 dBFS
+    pub const fn target_dbfs(self, target_dbfs: f32) -> AlcConfig {
+        AlcConfig {
+            target_dbfs,
+            ..self
+        }
+    }
+
+    /// Set the lowest and highest PGA gains the ALC may use.
+    ///
+    /// The lowest is from -12 dB to +30 dB and the highest is from -6.75 dB
+    /// to +35.25 dB, both in 6 dB steps.
+    pub const fn gain_limits_db(self, min_gain_db: f32, max_gain_db: f32) -> AlcConfig {
+        AlcConfig {
+            min_gain_db,
+            max_gain_db,
+            ..self
+        }
+    }
+
+    /// Set how long the level must stay low before the gain goes up
+    pub const fn hold(self, hold: Duration) -> AlcConfig {
+        AlcConfig { hold, ..self }
+    }
+
+    /// Set the time taken for each 0.75 dB step of gain reduction
+    pub const fn attack(self, attack: Duration) -> AlcConfig {
+        AlcConfig { attack, ..self }
+    }
+
+    /// Set the time taken for each 0.75 dB step of gain increase
+    pub const fn decay(self, decay: Duration) -> AlcConfig {
+        AlcConfig { decay, ..self }
+    }
+
+    /// Work out the register settings which get closest to the behaviour
+    /// asked for.
+    ///
+    /// Times are rounded to the nearest one on offer (on a log scale) for
+    /// the selected mode, and clamped to the longest. Returns `None` if the
+    /// target or either gain limit is out of range, or the lower gain limit
+    /// is above the upper one.
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use nau88c22::alc::{AlcConfig, AlcMode};
+    /// let settings = AlcConfig::new().compute().unwrap();
+    /// assert_eq!((settings.alcsl, settings.alcmxgain, settings.alcmngain), (11, 7, 0));
+    /// assert_eq!((settings.alcht, settings.alcatk, settings.alcdcy), (0, 2, 3));
+    /// // The same time is a different code in limiter mode
+    /// let settings = AlcConfig::new()
+    ///     .mode(AlcMode::Limiter)
+    ///     .attack(Duration::from_micros(500))
+    ///     .hold(Duration::from_secs(5))
+    ///     .compute()
+    ///     .unwrap();
+    /// assert_eq!((settings.alcatk, settings.alcht), (4, 10));
+    /// // Low targets need the optional table
+    /// let settings = AlcConfig::new().target_dbfs(-27.0).compute().unwrap();
+    /// assert_eq!((settings.alctblsel, settings.alcsl, settings.target_dbfs), (true, 1, -27.0));
+    /// assert!(AlcConfig::new().gain_limits_db(12.0, 5.25).compute().is_none());
+    /// ```
+    pub fn compute(&self) -> Option<AlcSettings> {
+        let (alctblsel, alcsl, target_dbfs) = match Self::TARGET_RANGE.code_for(self.target_dbfs) {
+            Some(code) => (false, code, Self::TARGET_RANGE.decibels(code)),
+            None => {
+                let code = Self::LOW_TARGET_RANGE.code_for(self.target_dbfs)?;
+                (true, code, Self::LOW_TARGET_RANGE.decibels(code))
+            }
+        };
+        if self.min_gain_db > self.max_gain_db {
+            return None;
+        }
+        let alcmngain = Self::MIN_GAIN_RANGE.code_for(self.min_gain_db)?;
+        let alcmxgain = Self::MAX_GAIN_RANGE.code_for(self.max_gain_db)?;
+        let (attack_times, decay_times) = match self.mode {
+            AlcMode::Normal => (&Self::NORMAL_ATTACK_TIMES_US, &Self::NORMAL_DECAY_TIMES_US),
+            AlcMode::Limiter => (
+                &Self::LIMITER_ATTACK_TIMES_US,
+                &Self::LIMITER_DECAY_TIMES_US,
+            ),
+        };
+        Some(AlcSettings {
+            alcm: self.mode == AlcMode::Limiter,
+            alcsl,
+            alctblsel,
+            alcmngain,
+            alcmxgain,
+            alcht: util::time_code(&Self::HOLD_TIMES_US, self.hold.as_micros() as f32),
+            alcatk: util::time_code(attack_times, self.attack.as_micros() as f32),
+            alcdcy: util::time_code(decay_times, self.decay.as_micros() as f32),
+            target_dbfs: target_dbfs.db(),
+        })
+    }
+}
+
+impl Default for AlcConfig {
+    fn default() -> Self {
+        AlcConfig::new()
+    }
+}
+
+/// The register settings for an [`AlcConfig`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlcSettings {
+    /// Limiter mode ([`registers::ALCControl3::alcm`])
+    ///
+    /// [`registers::ALCControl3::alcm`]: crate::registers::ALCControl3::alcm
+    pub alcm: bool,
+    /// The target level code ([`registers::ALCControl2::alcsl`])
+    ///
+    /// [`registers::ALCControl2::alcsl`]: crate::registers::ALCControl2::alcsl
+    pub alcsl: u8,
+    /// Use the optional target level table
+    /// ([`registers::AlcEnhancements1::alctblsel`])
+    ///
+    /// [`registers::AlcEnhancements1::alctblsel`]: crate::registers::AlcEnhancements1::alctblsel
+    pub alctblsel: bool,
+    /// The minimum gain code ([`registers::ALCControl1::alcmngain`])
+    ///
+    /// [`registers::ALCControl1::alcmngain`]: crate::registers::ALCControl1::alcmngain
+    pub alcmngain: u8,
+    /// The maximum gain code ([`registers::ALCControl1::alcmxgain`])
+    ///
+    /// [`registers::ALCControl1::alcmxgain`]: crate::registers::ALCControl1::alcmxgain
+    pub alcmxgain: u8,
+    /// The hold time code ([`registers::ALCControl2::alcht`])
+    ///
+    /// [`registers::ALCControl2::alcht`]: crate::registers::ALCControl2::alcht
+    pub alcht: u8,
+    /// The attack time code ([`registers::ALCControl3::alcatk`])
+    ///
+    /// [`registers::ALCControl3::alcatk`]: crate::registers::ALCControl3::alcatk
+    pub alcatk: u8,
+    /// The decay time code ([`registers::ALCControl3::alcdcy`])
+    ///
+    /// [`registers::ALCControl3::alcdcy`]: crate::registers::ALCControl3::alcdcy
+    pub alcdcy: u8,
+    /// The target level these settings give
+    pub target_dbfs: f32,
+}
+
+/// Which level detector a threshold is compared with
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LevelDetector {
+    /// The rectified peak detector (default)
+    Peak,
+    /// The peak-to-peak detector
+    PeakToPeak,
+}
+
+/// Describes the noise gate we want
+///
+/// When the input level is below the threshold, the noise gate stops the
+/// ALC from raising the gain - so background noise is not amplified during
+/// pauses.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoiseGateConfig {
+    threshold_db: f32,
+    detector: LevelDetector,
+}
+
+impl NoiseGateConfig {
+    /// The thresholds, in dB, indexed by [`registers::NoiseGate::alcnth`]
+    /// value
+    ///
+    /// [`registers::NoiseGate::alcnth`]: crate::registers::NoiseGate::alcnth
+    pub const THRESHOLDS_DB: [f32; 8] = [-39.0, -45.0, -51.0, -57.0, -63.0, -69.0, -75.0, -81.0];
+
+    /// Describe a noise gate with a threshold from -81 dB to -39 dB, using
+    /// the peak detector
+    pub const fn new(threshold_db: f32) -> NoiseGateConfig {
+        NoiseGateConfig {
+            threshold_db,
+            detector: LevelDetector::Peak,
+        }
+    }
+
+    /// Select the level detector used
+    /// ([`registers::AlcEnhancements1::alcngsel`])
+    ///
+    /// [`registers::AlcEnhancements1::alcngsel`]: crate::registers::AlcEnhancements1::alcngsel
+    pub const fn detector(self, detector: LevelDetector) -> NoiseGateConfig {
+        NoiseGateConfig { detector, ..self }
+    }
+
+    /// Find the threshold code closest to the one asked for.
+    ///
+    /// Returns `None` if the threshold is out of range.
+    ///
+    /// ```
+    /// use nau88c22::alc::NoiseGateConfig;
+    /// assert_eq!(NoiseGateConfig::new(-39.0).alcnth(), Some(0));
+    /// assert_eq!(NoiseGateConfig::new(-62.0).alcnth(), Some(4));
+    /// assert_eq!(NoiseGateConfig::new(-84.0).alcnth(), Some(7));
+    /// assert_eq!(NoiseGateConfig::new(-20.0).alcnth(), None);
+    /// assert_eq!(NoiseGateConfig::new(-85.0).alcnth(), None);
+    /// ```
+    pub fn alcnth(&self) -> Option<u8> {
+        util::level_code(&Self::THRESHOLDS_DB, self.threshold_db)
+    }
+}
+
+/// A snapshot of the ALC's live state
+///
+/// The detector levels are the highest of the two channels when both are
+/// under ALC control. They are converted with
+/// [`AlcStatus::detector_dbfs`], which assumes a linear scale on which the
+/// largest reading (511) is 0 dBFS - the datasheet does not give the
+/// scaling, so treat these levels as approximate.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlcStatus {
+    /// The left PGA gain ([`registers::AlcEnhancements1::alcgainl`])
+    ///
+    /// [`registers::AlcEnhancements1::alcgainl`]: crate::registers::AlcEnhancements1::alcgainl
+    pub left_gain: Decibels,
+    /// The right PGA gain ([`registers::AlcEnhancements2::alcgainr`])
+    ///
+    /// [`registers::AlcEnhancements2::alcgainr`]: crate::registers::AlcEnhancements2::alcgainr
+    pub right_gain: Decibels,
+    /// The peak detector level ([`registers::PeakDetectorRead::peakval`])
+    ///
+    /// [`registers::PeakDetectorRead::peakval`]: crate::registers::PeakDetectorRead::peakval
+    pub peak: Decibels,
+    /// The peak-to-peak detector level
+    /// ([`registers::P2PDetectorRead::p2pval`])
+    ///
+    /// [`registers::P2PDetectorRead::p2pval`]: crate::registers::P2PDetectorRead::p2pval
+    pub peak_to_peak: Decibels,
+    /// The noise gate is holding the gain
+    /// ([`registers::ControlAndStatus::nsgate`])
+    ///
+    /// [`registers::ControlAndStatus::nsgate`]: crate::registers::ControlAndStatus::nsgate
+    pub noise_gated: bool,
+}
+
+impl AlcStatus {
+    /// The detector reading assumed for a full scale signal
+    pub const DETECTOR_FULL_SCALE: u16 = 511;
+
+    /// Convert a detector reading to dBFS
+    ///
+    /// The datasheet does not say how [`registers::PeakDetectorRead::peakval`]
+    /// and [`registers::P2PDetectorRead::p2pval`] relate to the signal level.
+    /// This assumes both are linear in amplitude, with
+    /// [`AlcStatus::DETECTOR_FULL_SCALE`] as 0 dBFS; a reading of zero is
+    /// [`Decibels::MUTE`]. Use the raw register values if that assumption
+    /// does not hold for your part.
+    ///
+    /// [`registers::PeakDetectorRead::peakval`]: crate::registers::PeakDetectorRead::peakval
+    /// [`registers::P2PDetectorRead::p2pval`]: crate::registers::P2PDetectorRead::p2pval
+    ///
+    /// ```
+    /// use nau88c22::{alc::AlcStatus, volume::Decibels};
+    /// assert_eq!(AlcStatus::detector_dbfs(511), Decibels(0.0));
+    /// assert!((AlcStatus::detector_dbfs(255).db() + 6.03).abs() < 0.01);
+    /// assert!(AlcStatus::detector_dbfs(0).is_mute());
+    /// ```
+    pub fn detector_dbfs(value: u16) -> Decibels {
+        if value == 0 {
+            Decibels::MUTE
+        } else {
+            let ratio = f32::from(value.min(Self::DETECTOR_FULL_SCALE))
+                / f32::from(Self::DETECTOR_FULL_SCALE);
+            Decibels(20.0 * libm::log10f(ratio))
+        }
+    }
+}
+
+/// The lowest, highest and average of a series of readings
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LevelStats {
+    /// The lowest reading
+    pub min: Decibels,
+    /// The highest reading
+    pub max: Decibels,
+    /// The average reading
+    pub mean: Decibels,
+}
+
+/// A running summary of one kind of reading
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Accumulator {
+    min: f32,
+    max: f32,
+    sum: f32,
+}
+
+impl Accumulator {
+    const fn new() -> Accumulator {
+        Accumulator {
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            sum: 0.0,
+        }
+    }
+
+    fn add(&mut self, reading: Decibels, linear: bool) {
+        self.min = self.min.min(reading.db());
+        self.max = self.max.max(reading.db());
+        self.sum += if linear {
+            libm::powf(10.0, reading.db() / 20.0)
+        } else {
+            reading.db()
+        };
+    }
+
+    fn stats(&self, samples: u32, linear: bool) -> Option<LevelStats> {
+        if samples == 0 {
+            return None;
+        }
+        let mean = self.sum / samples as f32;
+        let mean = if !linear {
+            Decibels(mean)
+        } else if mean > 0.0 {
+            Decibels(20.0 * libm::log10f(mean))
+        } else {
+            Decibels::MUTE
+        };
+        Some(LevelStats {
+            min: Decibels(self.min),
+            max: Decibels(self.max),
+            mean,
+        })
+    }
+}
+
+/// Collects the minimum, maximum and average of a series of [`AlcStatus`]
+/// readings - for a record level meter, for example.
+///
+/// Gains are averaged in dB. Detector levels are averaged as amplitudes,
+/// so that moments of silence don't drag the average to minus infinity.
+///
+/// ```
+/// use nau88c22::{alc::{AlcStats, AlcStatus}, volume::Decibels};
+/// let mut stats = AlcStats::new();
+/// assert!(stats.peak().is_none());
+/// for (gain, peak) in [(0.0, 511), (6.0, 0)] {
+///     stats.add(&AlcStatus {
+///         left_gain: Decibels(gain),
+///         right_gain: Decibels(gain),
+///         peak: AlcStatus::detector_dbfs(peak),
+///         peak_to_peak: AlcStatus::detector_dbfs(peak),
+///         noise_gated: peak == 0,
+///     });
+/// }
+/// assert_eq!(stats.samples(), 2);
+/// assert_eq!(stats.gated_samples(), 1);
+/// assert_eq!(stats.left_gain().unwrap().mean, Decibels(3.0));
+/// let peak = stats.peak().unwrap();
+/// assert!(peak.min.is_mute());
+/// assert_eq!(peak.max, Decibels(0.0));
+/// assert!((peak.mean.db() + 6.02).abs() < 0.01);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlcStats {
+    samples: u32,
+    gated_samples: u32,
+    left_gain: Accumulator,
+    right_gain: Accumulator,
+    peak: Accumulator,
+    peak_to_peak: Accumulator,
+}
+
+impl AlcStats {
+    /// Start with no readings
+    pub const fn new() -> AlcStats {
+        AlcStats {
+            samples: 0,
+            gated_samples: 0,
+            left_gain: Accumulator::new(),
+            right_gain: Accumulator::new(),
+            peak: Accumulator::new(),
+            peak_to_peak: Accumulator::new(),
+        }
+    }
+
+    /// Add a reading
+    pub fn add(&mut self, status: &AlcStatus) {
+        self.samples += 1;
+        if status.noise_gated {
+            self.gated_samples += 1;
+        }
+        self.left_gain.add(status.left_gain, false);
+        self.right_gain.add(status.right_gain, false);
+        self.peak.add(status.peak, true);
+        self.peak_to_peak.add(status.peak_to_peak, true);
+    }
+
+    /// How many readings have been added
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// How many readings had the noise gate closed
+    pub fn gated_samples(&self) -> u32 {
+        self.gated_samples
+    }
+
+    /// The left PGA gain, or `None` if there are no readings
+    pub fn left_gain(&self) -> Option<LevelStats> {
+        self.left_gain.stats(self.samples, false)
+    }
+
+    /// The right PGA gain, or `None` if there are no readings
+    pub fn right_gain(&self) -> Option<LevelStats> {
+        self.right_gain.stats(self.samples, false)
+    }
+
+    /// The peak detector level, or `None` if there are no readings
+    pub fn peak(&self) -> Option<LevelStats> {
+        self.peak.stats(self.samples, true)
+    }
+
+    /// The peak-to-peak detector level, or `None` if there are no readings
+    pub fn peak_to_peak(&self) -> Option<LevelStats> {
+        self.peak_to_peak.stats(self.samples, true)
+    }
+}
+
+impl Default for AlcStats {
+    fn default() -> Self {
+        AlcStats::new()
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up and enable the noise gate, or disable it with `None`.
+    ///
+    /// The noise gate is part of the ALC, so this returns
+    /// [`Error::AlcDisabled`] if the ALC is off on both channels - call
+    /// [`Codec::configure_alc`] first. Returns the threshold used, or
+    /// [`Error::OutOfRange`] if the threshold is out of range.
+    ///
+    /// ```rust,no_run
+    /// # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+    /// use nau88c22::alc::{AlcChannels, AlcConfig, LevelDetector, NoiseGateConfig};
+    /// codec.configure_alc(AlcChannels::Both, &AlcConfig::new())?;
+    /// let gate = NoiseGateConfig::new(-57.0).detector(LevelDetector::PeakToPeak);
+    /// codec.configure_noise_gate(Some(gate))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn configure_noise_gate(
+        &mut self,
+        config: Option<NoiseGateConfig>,
+    ) -> Result<Option<f32>, Error<I::Error>> {
+        let Some(config) = config else {
+            self.modify_noisegate(|mut w| {
+                w.alcnen_set(false);
+                w
+            })?;
+            return Ok(None);
+        };
+        let alcnth = config.alcnth().ok_or(Error::OutOfRange)?;
+        if AlcChannels::from(self.read_alccontrol1()?.alcen()) == AlcChannels::Off {
+            return Err(Error::AlcDisabled);
+        }
+        self.modify_alcenhancements1(|mut w| {
+            w.alcngsel_set(config.detector == LevelDetector::PeakToPeak);
+            w
+        })?;
+        self.modify_noisegate(|mut w| {
+            w.alcnen_set(true);
+            w.alcnth_set(alcnth);
+            w
+        })?;
+        Ok(Some(NoiseGateConfig::THRESHOLDS_DB[usize::from(alcnth)]))
+    }
+
+    /// Is the noise gate currently holding the ALC gain?
+    ///
+    /// See [`registers::ControlAndStatus::nsgate`].
+    ///
+    /// [`registers::ControlAndStatus::nsgate`]: crate::registers::ControlAndStatus::nsgate
+    pub fn noise_gate_active(&mut self) -> Result<bool, Error<I::Error>> {
+        Ok(self.read_controlandstatus()?.nsgate())
+    }
+
+    /// Read the ALC's live gain, detector and noise gate state.
+    ///
+    /// These registers are read-only, so this needs an interface which can
+    /// read.
+    pub fn alc_status(&mut self) -> Result<AlcStatus, Error<I::Error>> {
+        let range = VolumeStage::Pga.range();
+        let left_gain = range.decibels(self.read_alcenhancements1()?.alcgainl());
+        let right_gain = range.decibels(self.read_alcenhancements2()?.alcgainr());
+        let peak = AlcStatus::detector_dbfs(self.read_peakdetectorread()?.peakval());
+        let peak_to_peak = AlcStatus::detector_dbfs(self.read_p2pdetectorread()?.p2pval());
+        let noise_gated = self.read_controlandstatus()?.nsgate();
+        Ok(AlcStatus {
+            left_gain,
+            right_gain,
+            peak,
+            peak_to_peak,
+            noise_gated,
+        })
+    }
+
+    /// Read the ALC state `samples` times, waiting `interval_us`
+    /// microseconds between each, and summarise the results.
+    ///
+    /// ```rust,no_run
+    /// # fn example<I, D>(codec: &mut nau88c22::Codec<I>, delay: &mut D) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface, D: embedded_hal::delay::DelayNs {
+    /// // Ten readings over 100 ms
+    /// let stats = codec.alc_stats(10, 10_000, delay)?;
+    /// if let Some(peak) = stats.peak() {
+    ///     let meter_dbfs = peak.max;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn alc_stats<D>(
+        &mut self,
+        samples: u32,
+        interval_us: u32,
+        delay: &mut D,
+    ) -> Result<AlcStats, Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        let mut stats = AlcStats::new();
+        for sample in 0..samples {
+            if sample != 0 {
+                delay.delay_us(interval_us);
+            }
+            stats.add(&self.alc_status()?);
+        }
+        Ok(stats)
+    }
+
+    /// Set up the ALC, and enable it on the given channels.
+    ///
+    /// Also turns on the PGA zero-cross detector for those channels, which
+    /// the ALC needs to change the gain quietly. Returns the settings used,
+    /// or [`Error::OutOfRange`] if the configuration is impossible (see
+    /// [`AlcConfig::compute`]).
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::alc::{AlcChannels, AlcConfig};
+    /// let mut i2c = MockI2c { registers: [0; 0x50], writes: Vec::new() };
+    /// // Both PGAs at 0 dB
+    /// i2c.registers[0x2D] = 0x010;
+    /// i2c.registers[0x2E] = 0x010;
+    /// let mut codec = nau88c22::Codec::new(i2c);
+    /// codec.configure_alc(AlcChannels::Left, &AlcConfig::new()).unwrap();
+    /// let i2c = codec.release();
+    /// // Zero-cross on the left PGA, latched by the right PGA's update bit
+    /// assert_eq!(i2c.writes[3..5], [(0x2D, 0x090), (0x2E, 0x110)]);
+    /// ```
+    pub fn configure_alc(
+        &mut self,
+        channels: AlcChannels,
+        config: &AlcConfig,
+    ) -> Result<AlcSettings, Error<I::Error>> {
+        let settings = config.compute().ok_or(Error::OutOfRange)?;
+        self.modify_alccontrol2(|mut w| {
+            w.alcht_set(settings.alcht);
+            w.alcsl_set(settings.alcsl);
+            w
+        })?;
+        self.modify_alccontrol3(|mut w| {
+            w.alcm_set(settings.alcm);
+            w.alcdcy_set(settings.alcdcy);
+            w.alcatk_set(settings.alcatk);
+            w
+        })?;
+        self.modify_alcenhancements1(|mut w| {
+            w.alctblsel_set(settings.alctblsel);
+            w
+        })?;
+        // A PGA write is held back until the right channel is written with
+        // its update bit set, so always finish with the right channel
+        let left = channels.includes(Channel::Left);
+        let right = channels.includes(Channel::Right);
+        if left {
+            self.modify_leftinputpgagain(|mut w| {
+                w.lpgazc_set(true);
+                w
+            })?;
+        }
+        if left || right {
+            self.modify_rightinputpgagain(|mut w| {
+                if right {
+                    w.rpgazc_set(true);
+                }
+                w.rpgau_set(true);
+                w
+            })?;
+        }
+        self.modify_alccontrol1(|mut w| {
+            w.alcen_set(channels.into());
+            w.alcmxgain_set(settings.alcmxgain);
+            w.alcmngain_set(settings.alcmngain);
+            w
+        })?;
+        Ok(settings)
+    }
+}
+
+// End of file
@@ -0,0 +1,194 @@
+//! An async driver for the register interface on the Nuvoton NAU88C22 CODEC
+//!
+//! Only available with the `async` feature enabled. Works in I²C mode only.
+//!
+//! ```rust,no_run
+//! # async fn example<I>(i2c: I) -> Result<(), nau88c22::Error<I::Error>> where I: embedded_hal_async::i2c::I2c {
+//! let mut codec = nau88c22::asynch::AsyncCodec::new(i2c);
+//! codec.reset().await?;
+//! codec.check_device_id().await?;
+//! codec.modify_powermanagement1(|mut w| {
+//!     w.iobufen_set(true);
+//!     w.dcbufen_set(true);
+//!     w
+//! }).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use embedded_hal_async::i2c::I2c;
+
+use crate::{
+    interface::{read_frame, write_frame, DEVICE_ADDR},
+    registers, Error, Register,
+};
+
+/// Represents the NAU882CC CODEC, driven with async I²C transactions
+///
+/// This mirrors [`Codec`](crate::Codec), except every method is an `async fn`.
+/// The register types in [`registers`] are shared between the two.
+///
+/// ```
+/// # use embedded_hal_async::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+/// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// # pub enum Error { }
+/// # impl hali2c::Error for Error {
+/// #     fn kind(&self) -> ErrorKind {
+/// #         ErrorKind::Other
+/// #     }
+/// # }
+/// /// Stores every register, and records every register write
+/// struct MockI2c {
+///     registers: [u16; 0x50],
+///     writes: Vec<(u8, u16)>,
+/// }
+/// # impl hali2c::ErrorType for MockI2c {
+/// #     type Error = Error;
+/// # }
+/// impl I2c<SevenBitAddress> for MockI2c {
+///     async fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+///         match operations {
+///             [Operation::Write(data)] => {
+///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+///                 self.registers[usize::from(data[0] >> 1)] = value;
+///                 self.writes.push((data[0] >> 1, value));
+///             }
+///             [Operation::Write(data), Operation::Read(buffer)] => {
+///                 let value = self.registers[usize::from(data[0] >> 1)];
+///                 buffer.copy_from_slice(&value.to_be_bytes());
+///             }
+///             _ => unimplemented!(),
+///         }
+///         Ok(())
+///     }
+/// }
+/// # /// Run a future which never has to wait
+/// # fn block_on<F: core::future::Future>(future: F) -> F::Output {
+/// #     let mut context = core::task::Context::from_waker(core::task::Waker::noop());
+/// #     let mut future = core::pin::pin!(future);
+/// #     loop {
+/// #         if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+/// #             return output;
+/// #         }
+/// #     }
+/// # }
+///
+/// use nau88c22::{asynch::AsyncCodec, registers::LHPVolume};
+/// let mut i2c = MockI2c { registers: [0; 0x50], writes: Vec::new() };
+/// i2c.registers[0x3F] = 0x01A;
+/// let mut codec = AsyncCodec::new(i2c);
+/// block_on(async {
+///     codec.check_device_id().await.unwrap();
+///     let mut volume = LHPVolume::default();
+///     volume.lhpvu_set(true);
+///     volume.lhpgain_set(0x20);
+///     codec.write_lhpvolume(volume).await.unwrap();
+///     assert_eq!(codec.read_lhpvolume().await.unwrap().lhpgain(), 0x20);
+///     // The update bit is cleared before the closure sees the value
+///     codec
+///         .modify_lhpvolume(|mut w| {
+///             assert!(!w.lhpvu());
+///             w.lhpmute_set(true);
+///             w
+///         })
+///         .await
+///         .unwrap();
+/// });
+/// let i2c = codec.release();
+/// assert_eq!(i2c.writes, [(0x34, 0x120), (0x34, 0x060)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncCodec<I> {
+    interface: I,
+}
+
+impl<I> AsyncCodec<I>
+where
+    I: I2c,
+{
+    /// Expected in the Device ID register
+    const DEVICE_ID: u16 = 0x1A;
+
+    /// Create a new CODEC object.
+    ///
+    /// Holds on to the given I²C interface so it can perform I²C transactions
+    /// whenever its methods are called.
+    pub const fn new(interface: I) -> AsyncCodec<I> {
+        AsyncCodec { interface }
+    }
+
+    /// Give back the I²C interface
+    pub fn release(self) -> I {
+        self.interface
+    }
+
+    /// Read the Device ID register as a check we actually have a CODEC
+    pub async fn check_device_id(&mut self) -> Result<(), Error<I::Error>> {
+        let device_id = self.read_register(Register::DeviceId).await?;
+        #[cfg(feature = "defmt")]
+        defmt::info!("Device ID = 0x{:03x}", device_id);
+        if device_id == Self::DEVICE_ID {
+            Ok(())
+        } else {
+            Err(Error::WrongDeviceId)
+        }
+    }
+
+    /// Reset the chip
+    pub async fn reset(&mut self) -> Result<(), Error<I::Error>> {
+        // write anything to this register to reset it
+        self.write_register(Register::SoftwareReset, 0x1FF).await
+    }
+
+    register_accessors!([async] [.await]);
+
+    /// Read a nine-bit register from the chip.
+    ///
+    /// The value comes back in the lowest 9 bits of a `u16`.
+    pub async fn read_register(&mut self, register: Register) -> Result<u16, Error<I::Error>> {
+        let mut buffer = [0u8; 2];
+        self.interface
+            .write_read(DEVICE_ADDR, &[(register as u8) * 2], &mut buffer)
+            .await
+            .map_err(Error::I2c)?;
+        Ok(read_frame(buffer))
+    }
+
+    /// Write a nine-bit register to the chip.
+    ///
+    /// The value should be given in the lowest 9 bits of a `u16`.
+    pub async fn write_register(
+        &mut self,
+        register: Register,
+        value: u16,
+    ) -> Result<(), Error<I::Error>> {
+        self.interface
+            .write(DEVICE_ADDR, &write_frame(register, value))
+            .await
+            .map_err(Error::I2c)
+    }
+
+    /// Modify a nine-bit register on the chip.
+    ///
+    /// Performs a register read, then runs the given closure `f`, then performs
+    /// a register write. Any write-only bits are cleared before the value is
+    /// given to `f`.
+    pub async fn modify_register<F>(
+        &mut self,
+        register: Register,
+        f: F,
+    ) -> Result<(), Error<I::Error>>
+    where
+        F: FnOnce(u16) -> u16,
+    {
+        let value = self.read_register(register).await? & !register.write_only_mask();
+        let new_value = f(value);
+        self.write_register(register, new_value).await
+    }
+}
+
+// End of file
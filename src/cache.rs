@@ -0,0 +1,119 @@
+//! A shadow copy of the CODEC registers
+//!
+//! Lets the driver remember what it last wrote to each register, so that
+//! read-modify-write operations keep working on write-only bits, and on
+//! control interfaces (like 3-wire SPI) that cannot read registers at all.
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Register;
+
+/// How a [`Codec`](crate::Codec) uses its register cache
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CacheMode {
+    /// Nothing is cached, and every read goes to the chip (default)
+    #[default]
+    Disabled,
+    /// Every value read from or written to the chip is recorded, but reads
+    /// still go to the chip
+    WriteThrough,
+    /// Every value read from or written to the chip is recorded, and reads are
+    /// served from the cache wherever possible.
+    ///
+    /// Registers which the chip can change by itself (see
+    /// [`Register::is_volatile`] and [`Register::read_only_mask`]) are always
+    /// read from the chip, but a read-modify-write of their settings uses the
+    /// cache.
+    ReadFromCache,
+}
+
+/// Holds the last known value of every register
+///
+/// ```
+/// use nau88c22::{Register, RegisterCache};
+/// let mut cache = RegisterCache::new();
+/// assert_eq!(cache.get(Register::LHPVolume), None);
+/// // The update bit is write-only, so it isn't remembered
+/// cache.set(Register::LHPVolume, 0x139);
+/// assert_eq!(cache.get(Register::LHPVolume), Some(0x039));
+/// // The ALC gain is read-only, so only the ALC settings are remembered
+/// cache.set(Register::AlcEnhancements1, 0x1E5);
+/// assert_eq!(cache.get(Register::AlcEnhancements1), Some(0x1C0));
+/// cache.load_defaults();
+/// assert_eq!(cache.get(Register::AudioInterface), Some(0x050));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegisterCache {
+    /// One entry per register address
+    values: [u16; Self::NUM_ENTRIES],
+    /// Bit `n` is set when `values[n]` holds a known value
+    valid: u128,
+}
+
+impl RegisterCache {
+    /// One more than the highest register address
+    const NUM_ENTRIES: usize = 0x50;
+
+    /// Create an empty cache, where no register value is known
+    pub const fn new() -> RegisterCache {
+        RegisterCache {
+            values: [0; Self::NUM_ENTRIES],
+            valid: 0,
+        }
+    }
+
+    /// Get the cached value of a register, if we know it.
+    ///
+    /// Write-only bits (see [`Register::write_only_mask`]) and read-only bits
+    /// (see [`Register::read_only_mask`]) are always clear.
+    pub fn get(&self, register: Register) -> Option<u16> {
+        let idx = register as usize;
+        if (self.valid & (1 << idx)) != 0 {
+            Some(self.values[idx])
+        } else {
+            None
+        }
+    }
+
+    /// Record the value of a register.
+    ///
+    /// Write-only bits are dropped, as they do not hold any state, and so are
+    /// read-only bits, as the chip changes them by itself.
+    pub fn set(&mut self, register: Register, value: u16) {
+        let idx = register as usize;
+        self.values[idx] = value & 0x1FF & !register.write_only_mask() & !register.read_only_mask();
+        self.valid |= 1 << idx;
+    }
+
+    /// Forget the value of one register
+    pub fn forget(&mut self, register: Register) {
+        self.valid &= !(1 << register as usize);
+    }
+
+    /// Forget the value of every register
+    pub fn invalidate(&mut self) {
+        self.valid = 0;
+    }
+
+    /// Set every register to its power-on default value, as given by
+    /// [`Register::default_value`].
+    ///
+    /// This is what the chip contains after a software reset.
+    pub fn load_defaults(&mut self) {
+        self.invalidate();
+        for &register in Register::readable() {
+            self.set(register, register.default_value());
+        }
+    }
+}
+
+impl Default for RegisterCache {
+    fn default() -> Self {
+        RegisterCache::new()
+    }
+}
+
+// End of file
@@ -0,0 +1,233 @@
+//! Clock tree configuration for the NAU88C22
+//!
+//! The CODEC needs an internal master clock of 256 × fs. This is made either
+//! by dividing down the MCLK input, or by dividing down the PLL output. In
+//! master mode, the CODEC also divides the internal master clock to make BCLK
+//! and FS.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::clocks::ClockConfig;
+//! // 12 MHz crystal, 48 kHz audio, generating BCLK and FS for 16-bit stereo
+//! let config = ClockConfig::new(12_000_000, 48_000)
+//!     .use_pll(true)
+//!     .master(true)
+//!     .frame(16, 2);
+//! codec.set_clocks(&config)?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    pll::{PllError, PllSettings},
+    Codec, Error, Interface,
+};
+
+/// The ways that a clock configuration can be impossible
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockError {
+    /// MCLK is not 1, 1.5, 2, 3, 4, 6, 8 or 12 times 256 × fs, so the PLL
+    /// must be used
+    NoMclkDivider,
+    /// The PLL cannot make this sample rate from this MCLK
+    Pll(PllError),
+    /// The frame (bits per channel × channels) is empty, or longer than 256
+    /// bits, so no BCLK divider fits
+    NoBclkDivider,
+}
+
+/// Describes how the CODEC should be clocked
+///
+/// By default the CODEC is a slave, clocked directly from MCLK, with 32-bit
+/// stereo frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockConfig {
+    mclk_hz: u32,
+    sample_rate_hz: u32,
+    use_pll: bool,
+    master: bool,
+    bits_per_channel: u8,
+    channels: u8,
+}
+
+impl ClockConfig {
+    /// The sample rates that [`registers::ClockControl2::smplr`] can
+    /// indicate, indexed by register value.
+    ///
+    /// [`registers::ClockControl2::smplr`]: crate::registers::ClockControl2::smplr
+    pub const SAMPLE_RATE_HINTS: [u32; 6] = [48_000, 32_000, 24_000, 16_000, 12_000, 8_000];
+
+    /// Describe a CODEC fed with the given MCLK, running at the given sample
+    /// rate.
+    pub const fn new(mclk_hz: u32, sample_rate_hz: u32) -> ClockConfig {
+        ClockConfig {
+            mclk_hz,
+            sample_rate_hz,
+            use_pll: false,
+            master: false,
+            bits_per_channel: 32,
+            channels: 2,
+        }
+    }
+
+    /// Make the internal master clock with the PLL, instead of dividing MCLK
+    /// directly
+    pub const fn use_pll(self, use_pll: bool) -> ClockConfig {
+        ClockConfig { use_pll, ..self }
+    }
+
+    /// Drive BCLK and FS from the CODEC, instead of taking them as inputs
+    pub const fn master(self, master: bool) -> ClockConfig {
+        ClockConfig { master, ..self }
+    }
+
+    /// Set the number of BCLK cycles per channel, and the number of channels
+    /// per frame.
+    ///
+    /// Only used in master mode, to pick the BCLK divider.
+    pub const fn frame(self, bits_per_channel: u8, channels: u8) -> ClockConfig {
+        ClockConfig {
+            bits_per_channel,
+            channels,
+            ..self
+        }
+    }
+
+    /// Work out the register settings for this configuration
+    ///
+    /// ```
+    /// use nau88c22::clocks::ClockConfig;
+    /// // 256 x 48 kHz, so divide by 1
+    /// let settings = ClockConfig::new(12_288_000, 48_000).master(true).frame(16, 2).compute().unwrap();
+    /// assert_eq!(settings.mclksel, 0);
+    /// // 256 / 32 = 8
+    /// assert_eq!(settings.bclksel, 3);
+    /// assert_eq!(settings.smplr, 0);
+    /// // 24-bit stereo gets a 64 fs BCLK
+    /// let settings = ClockConfig::new(12_288_000, 48_000).master(true).frame(24, 2).compute().unwrap();
+    /// assert_eq!(settings.bclksel, 2);
+    /// // 44.1 kHz can't be made from 12.288 MHz without the PLL
+    /// assert!(ClockConfig::new(12_288_000, 44_100).compute().is_err());
+    /// assert!(ClockConfig::new(12_288_000, 44_100).use_pll(true).compute().is_ok());
+    /// ```
+    pub fn compute(&self) -> Result<ClockSettings, ClockError> {
+        let (pll, mclksel) = if self.use_pll {
+            let pll =
+                PllSettings::compute(self.mclk_hz, self.sample_rate_hz).map_err(ClockError::Pll)?;
+            (Some(pll), pll.mclksel)
+        } else {
+            let mclksel = PllSettings::MCLKSEL_HALVES
+                .iter()
+                .position(|halves| {
+                    u64::from(self.mclk_hz) * 2
+                        == 256 * u64::from(self.sample_rate_hz) * u64::from(*halves)
+                })
+                .ok_or(ClockError::NoMclkDivider)?;
+            (None, mclksel as u8)
+        };
+        let bclksel = if self.master {
+            let frame_bits = u32::from(self.bits_per_channel) * u32::from(self.channels);
+            if frame_bits == 0 || frame_bits > 256 {
+                return Err(ClockError::NoBclkDivider);
+            }
+            // Pick the fastest divider that gives enough BCLK cycles per frame.
+            // Divider `n` gives 256 / 2^n BCLKs per frame.
+            (0..=5u8)
+                .rev()
+                .find(|n| (256 >> n) >= frame_bits)
+                .ok_or(ClockError::NoBclkDivider)?
+        } else {
+            0
+        };
+        Ok(ClockSettings {
+            pll,
+            mclksel,
+            bclksel,
+            master: self.master,
+            smplr: Self::sample_rate_hint(self.sample_rate_hz),
+        })
+    }
+
+    /// Find the [`registers::ClockControl2::smplr`] value closest to the
+    /// given sample rate
+    ///
+    /// [`registers::ClockControl2::smplr`]: crate::registers::ClockControl2::smplr
+    pub fn sample_rate_hint(sample_rate_hz: u32) -> u8 {
+        let mut best = 0;
+        for (idx, hint) in Self::SAMPLE_RATE_HINTS.iter().enumerate() {
+            if hint.abs_diff(sample_rate_hz)
+                < Self::SAMPLE_RATE_HINTS[best].abs_diff(sample_rate_hz)
+            {
+                best = idx;
+            }
+        }
+        best as u8
+    }
+}
+
+/// The register settings for a [`ClockConfig`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClockSettings {
+    /// The PLL settings, if the PLL is used
+    pub pll: Option<PllSettings>,
+    /// The master clock divider ([`registers::ClockControl1::mclksel`])
+    ///
+    /// [`registers::ClockControl1::mclksel`]: crate::registers::ClockControl1::mclksel
+    pub mclksel: u8,
+    /// The BCLK divider ([`registers::ClockControl1::bclksel`])
+    ///
+    /// [`registers::ClockControl1::bclksel`]: crate::registers::ClockControl1::bclksel
+    pub bclksel: u8,
+    /// Whether BCLK and FS are outputs ([`registers::ClockControl1::clkioen`])
+    ///
+    /// [`registers::ClockControl1::clkioen`]: crate::registers::ClockControl1::clkioen
+    pub master: bool,
+    /// The filter sample rate hint ([`registers::ClockControl2::smplr`])
+    ///
+    /// [`registers::ClockControl2::smplr`]: crate::registers::ClockControl2::smplr
+    pub smplr: u8,
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up the whole clock tree in one go.
+    ///
+    /// Checks the configuration is possible before writing anything. If the
+    /// PLL is used, it is powered up before it is selected as the master
+    /// clock source. If not, it is powered down once it is no longer selected.
+    pub fn set_clocks(&mut self, config: &ClockConfig) -> Result<ClockSettings, Error<I::Error>> {
+        let settings = config.compute().map_err(Error::Clock)?;
+        if let Some(pll) = settings.pll {
+            self.write_pll(&pll)?;
+        }
+        self.modify_clockcontrol1(|mut w| {
+            w.clkm_set(settings.pll.is_some());
+            w.mclksel_set(settings.mclksel);
+            w.bclksel_set(settings.bclksel);
+            w.clkioen_set(settings.master);
+            w
+        })?;
+        self.modify_clockcontrol2(|mut w| {
+            w.smplr_set(settings.smplr);
+            w
+        })?;
+        if settings.pll.is_none() {
+            self.modify_powermanagement1(|mut w| {
+                w.pllen_set(false);
+                w
+            })?;
+        }
+        Ok(settings)
+    }
+}
+
+// End of file
@@ -0,0 +1,544 @@
+//! The five-band equalizer in the NAU88C22
+//!
+//! Band 1 is a low shelf, bands 2 to 4 are peaking filters with a narrow or
+//! wide bandwidth, and band 5 is a high shelf. Each band has four
+//! frequencies to choose from, and a gain of -12 dB to +12 dB in 1 dB steps.
+//! The equalizer works on either the ADC or the DAC path, but not both.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::eq::{EqBand, EqPath, Equalizer};
+//! let mut eq = Equalizer::new(EqPath::Dac);
+//! // A little more bass, a little less harshness
+//! eq.bands[0] = EqBand::new(100, 4);
+//! eq.bands[3] = EqBand::new(3000, -3).wide(true);
+//! codec.set_equalizer(&eq)?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{registers, Codec, Error, Interface};
+
+/// Which digital audio stream the equalizer works on
+/// ([`registers::EQ1HighCutoff::eqm`])
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EqPath {
+    /// The stream from the ADC
+    Adc,
+    /// The stream to the DAC (default)
+    Dac,
+}
+
+/// The settings for one equalizer band
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EqBand {
+    /// The centre (or, for the shelves, cut-off) frequency. The nearest
+    /// supported frequency is used.
+    pub centre_hz: u32,
+    /// The gain, from -12 dB to +12 dB
+    pub gain_db: i8,
+    /// Use the wide bandwidth. Ignored for bands 1 and 5.
+    pub wide: bool,
+}
+
+impl EqBand {
+    /// Describe a narrow band at the given frequency and gain
+    pub const fn new(centre_hz: u32, gain_db: i8) -> EqBand {
+        EqBand {
+            centre_hz,
+            gain_db,
+            wide: false,
+        }
+    }
+
+    /// Use the wide bandwidth
+    pub const fn wide(self, wide: bool) -> EqBand {
+        EqBand { wide, ..self }
+    }
+}
+
+/// The equalizer register contents
+#[derive(Debug, Default)]
+pub struct EqRegisters {
+    /// Band 1, and the path selection
+    pub eq1: registers::EQ1HighCutoff,
+    /// Band 2
+    pub eq2: registers::EQ2Peak1,
+    /// Band 3
+    pub eq3: registers::EQ3Peak2,
+    /// Band 4
+    pub eq4: registers::EQ4Peak3,
+    /// Band 5
+    pub eq5: registers::EQ5LowCutoff,
+}
+
+impl EqRegisters {
+    /// Get the raw `(frequency code, gain code, wide)` for each band
+    pub fn band_codes(&self) -> [(u8, u8, bool); 5] {
+        [
+            (self.eq1.eq1cf(), self.eq1.eq1gc(), false),
+            (self.eq2.eq2cf(), self.eq2.eq2gc(), self.eq2.eq2bw()),
+            (self.eq3.eq3cf(), self.eq3.eq3gc(), self.eq3.eq3bw()),
+            (self.eq4.eq4cf(), self.eq4.eq4gc(), self.eq4.eq4bw()),
+            (self.eq5.eq5cf(), self.eq5.eq5gc(), false),
+        ]
+    }
+}
+
+/// A complete equalizer setting
+///
+/// ```
+/// use nau88c22::eq::{EqBand, EqPath, Equalizer};
+/// let mut eq = Equalizer::new(EqPath::Adc);
+/// eq.bands[2] = EqBand::new(1000, -6).wide(true);
+/// let regs = eq.to_registers().unwrap();
+/// // 1.1 kHz, and -6 dB is code 18
+/// assert_eq!((regs.eq3.eq3cf(), regs.eq3.eq3gc(), regs.eq3.eq3bw()), (2, 18, true));
+/// assert_eq!(regs.eq1.eqm(), false);
+/// assert_eq!(Equalizer::from_registers(&regs).bands[2], EqBand::new(1100, -6).wide(true));
+/// // Gains beyond 12 dB are rejected
+/// eq.bands[0].gain_db = 13;
+/// assert!(eq.to_registers().is_none());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Equalizer {
+    /// Which stream the equalizer works on
+    pub path: EqPath,
+    /// The five bands, from lowest to highest frequency
+    pub bands: [EqBand; 5],
+}
+
+impl Equalizer {
+    /// The frequencies available in each band, indexed by frequency code
+    pub const BAND_FREQUENCIES_HZ: [[u32; 4]; 5] = [
+        [80, 105, 135, 175],
+        [230, 300, 385, 500],
+        [650, 850, 1100, 1400],
+        [1800, 2400, 3200, 4100],
+        [5300, 6900, 9000, 11700],
+    ];
+
+    /// The largest boost or cut, in dB
+    pub const MAX_GAIN_DB: i8 = 12;
+
+    /// A flat equalizer, at the default frequencies, on the given path
+    pub const fn new(path: EqPath) -> Equalizer {
+        let mut bands = [EqBand::new(0, 0); 5];
+        let mut idx = 0;
+        while idx < bands.len() {
+            bands[idx].centre_hz = Self::BAND_FREQUENCIES_HZ[idx][1];
+            idx += 1;
+        }
+        Equalizer { path, bands }
+    }
+
+    /// Find the frequency code closest to the given frequency, on a
+    /// logarithmic scale
+    pub fn frequency_code(band: usize, centre_hz: u32) -> u8 {
+        let target = centre_hz.max(1) as f32;
+        let distance = |hz: u32| {
+            let ratio = hz as f32 / target;
+            if ratio < 1.0 {
+                1.0 / ratio
+            } else {
+                ratio
+            }
+        };
+        let mut best = 0;
+        for (idx, hz) in Self::BAND_FREQUENCIES_HZ[band].iter().enumerate() {
+            if distance(*hz) < distance(Self::BAND_FREQUENCIES_HZ[band][best]) {
+                best = idx;
+            }
+        }
+        best as u8
+    }
+
+    /// Convert a gain to a gain code, where `12` is 0 dB
+    fn gain_code(gain_db: i8) -> Option<u8> {
+        if gain_db.unsigned_abs() > Self::MAX_GAIN_DB as u8 {
+            None
+        } else {
+            Some((Self::MAX_GAIN_DB - gain_db) as u8)
+        }
+    }
+
+    /// Work out the register contents.
+    ///
+    /// Returns `None` if any gain is outside ±12 dB.
+    pub fn to_registers(&self) -> Option<EqRegisters> {
+        let mut codes = [(0u8, 0u8, false); 5];
+        for (idx, (band, code)) in self.bands.iter().zip(codes.iter_mut()).enumerate() {
+            *code = (
+                Self::frequency_code(idx, band.centre_hz),
+                Self::gain_code(band.gain_db)?,
+                band.wide,
+            );
+        }
+        let mut regs = EqRegisters::default();
+        regs.eq1.eqm_set(self.path == EqPath::Dac);
+        regs.eq1.eq1cf_set(codes[0].0);
+        regs.eq1.eq1gc_set(codes[0].1);
+        regs.eq2.eq2cf_set(codes[1].0);
+        regs.eq2.eq2gc_set(codes[1].1);
+        regs.eq2.eq2bw_set(codes[1].2);
+        regs.eq3.eq3cf_set(codes[2].0);
+        regs.eq3.eq3gc_set(codes[2].1);
+        regs.eq3.eq3bw_set(codes[2].2);
+        regs.eq4.eq4cf_set(codes[3].0);
+        regs.eq4.eq4gc_set(codes[3].1);
+        regs.eq4.eq4bw_set(codes[3].2);
+        regs.eq5.eq5cf_set(codes[4].0);
+        regs.eq5.eq5gc_set(codes[4].1);
+        Some(regs)
+    }
+
+    /// Decode the register contents.
+    ///
+    /// The reserved gain codes above `24` are read as -12 dB.
+    pub fn from_registers(regs: &EqRegisters) -> Equalizer {
+        let mut eq = Equalizer::new(if regs.eq1.eqm() {
+            EqPath::Dac
+        } else {
+            EqPath::Adc
+        });
+        for (idx, (band, (cf, gc, wide))) in eq.bands.iter_mut().zip(regs.band_codes()).enumerate()
+        {
+            *band = EqBand {
+                centre_hz: Self::BAND_FREQUENCIES_HZ[idx][usize::from(cf & 3)],
+                gain_db: Self::MAX_GAIN_DB - gc.min(24) as i8,
+                wide,
+            };
+        }
+        eq
+    }
+}
+
+/// One biquad section, normalised so that `a0` is 1
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+}
+
+impl Biquad {
+    /// The shape of a band, from the Audio EQ Cookbook (R. Bristow-Johnson)
+    fn new(shape: BandShape, centre_hz: f32, gain_db: f32, sample_rate_hz: f32) -> Biquad {
+        let a = libm::powf(10.0, gain_db / 40.0);
+        let w0 = 2.0 * core::f32::consts::PI * centre_hz / sample_rate_hz;
+        let cos_w0 = libm::cosf(w0);
+        let sin_w0 = libm::sinf(w0);
+        let (num, den) = match shape {
+            BandShape::Peak { q } => {
+                let alpha = sin_w0 / (2.0 * q);
+                (
+                    [1.0 + alpha * a, -2.0 * cos_w0, 1.0 - alpha * a],
+                    [1.0 + alpha / a, -2.0 * cos_w0, 1.0 - alpha / a],
+                )
+            }
+            BandShape::LowShelf => {
+                // Shelf slope of 1
+                let k = 2.0 * libm::sqrtf(a) * sin_w0 / core::f32::consts::SQRT_2;
+                (
+                    [
+                        a * ((a + 1.0) - (a - 1.0) * cos_w0 + k),
+                        2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                        a * ((a + 1.0) - (a - 1.0) * cos_w0 - k),
+                    ],
+                    [
+                        (a + 1.0) + (a - 1.0) * cos_w0 + k,
+                        -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                        (a + 1.0) + (a - 1.0) * cos_w0 - k,
+                    ],
+                )
+            }
+            BandShape::HighShelf => {
+                // Shelf slope of 1
+                let k = 2.0 * libm::sqrtf(a) * sin_w0 / core::f32::consts::SQRT_2;
+                (
+                    [
+                        a * ((a + 1.0) + (a - 1.0) * cos_w0 + k),
+                        -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                        a * ((a + 1.0) + (a - 1.0) * cos_w0 - k),
+                    ],
+                    [
+                        (a + 1.0) - (a - 1.0) * cos_w0 + k,
+                        2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                        (a + 1.0) - (a - 1.0) * cos_w0 - k,
+                    ],
+                )
+            }
+        };
+        Biquad {
+            b: [num[0] / den[0], num[1] / den[0], num[2] / den[0]],
+            a: [den[1] / den[0], den[2] / den[0]],
+        }
+    }
+
+    /// The magnitude response, in dB, at `w` radians per sample
+    fn magnitude_db(&self, w: f32) -> f32 {
+        let (cos1, sin1) = (libm::cosf(w), libm::sinf(w));
+        let (cos2, sin2) = (libm::cosf(2.0 * w), libm::sinf(2.0 * w));
+        let power = |c0: f32, c1: f32, c2: f32| {
+            let re = c0 + c1 * cos1 + c2 * cos2;
+            let im = c1 * sin1 + c2 * sin2;
+            re * re + im * im
+        };
+        let num = power(self.b[0], self.b[1], self.b[2]);
+        let den = power(1.0, self.a[0], self.a[1]);
+        10.0 * libm::log10f(num / den)
+    }
+}
+
+/// The shape of an equalizer band
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum BandShape {
+    LowShelf,
+    Peak { q: f32 },
+    HighShelf,
+}
+
+/// A model of the equalizer's frequency response
+///
+/// The datasheet gives the band frequencies but not the filter shapes, so
+/// this models band 1 and band 5 as shelves with a slope of 1, and bands 2
+/// to 4 as peaking filters with a Q of [`EqResponse::NARROW_Q`] or
+/// [`EqResponse::WIDE_Q`]. It is a tuning aid, and may differ from the
+/// silicon by a dB or so.
+///
+/// ```
+/// use nau88c22::eq::{EqBand, EqPath, EqResponse, Equalizer};
+/// let flat = EqResponse::new(&Equalizer::new(EqPath::Dac), 48_000.0);
+/// assert!(flat.magnitude_db(1000.0).abs() < 0.01);
+///
+/// let mut eq = Equalizer::new(EqPath::Dac);
+/// eq.bands[2] = EqBand::new(1100, 6);
+/// let response = EqResponse::new(&eq, 48_000.0);
+/// assert!((response.magnitude_db(1100.0) - 6.0).abs() < 0.01);
+/// assert!(response.magnitude_db(100.0).abs() < 0.1);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EqResponse {
+    bands: [Biquad; 5],
+    sample_rate_hz: f32,
+}
+
+impl EqResponse {
+    /// The Q used to model a narrow peaking band
+    pub const NARROW_Q: f32 = 2.0;
+    /// The Q used to model a wide peaking band
+    pub const WIDE_Q: f32 = 0.7;
+
+    /// Model an equalizer setting, at the given sample rate.
+    ///
+    /// Frequencies are moved to the nearest supported ones, and gains are
+    /// limited to ±12 dB, as they would be on the chip.
+    pub fn new(eq: &Equalizer, sample_rate_hz: f32) -> EqResponse {
+        let mut bands = [Biquad {
+            b: [1.0, 0.0, 0.0],
+            a: [0.0, 0.0],
+        }; 5];
+        for (idx, (biquad, band)) in bands.iter_mut().zip(eq.bands.iter()).enumerate() {
+            let cf = Equalizer::frequency_code(idx, band.centre_hz);
+            *biquad = Self::band(idx, cf, band.gain_db, band.wide, sample_rate_hz);
+        }
+        EqResponse {
+            bands,
+            sample_rate_hz,
+        }
+    }
+
+    /// Model the given equalizer register contents, at the given sample rate
+    pub fn from_registers(regs: &EqRegisters, sample_rate_hz: f32) -> EqResponse {
+        Self::new(&Equalizer::from_registers(regs), sample_rate_hz)
+    }
+
+    /// The gain of the equalizer, in dB, at the given frequency
+    pub fn magnitude_db(&self, freq_hz: f32) -> f32 {
+        let w = 2.0 * core::f32::consts::PI * freq_hz / self.sample_rate_hz;
+        self.bands.iter().map(|band| band.magnitude_db(w)).sum()
+    }
+
+    /// Make the biquad for one band, from its register codes
+    fn band(idx: usize, cf: u8, gain_db: i8, wide: bool, sample_rate_hz: f32) -> Biquad {
+        let shape = match idx {
+            0 => BandShape::LowShelf,
+            4 => BandShape::HighShelf,
+            _ if wide => BandShape::Peak { q: Self::WIDE_Q },
+            _ => BandShape::Peak { q: Self::NARROW_Q },
+        };
+        let centre_hz = Equalizer::BAND_FREQUENCIES_HZ[idx][usize::from(cf & 3)];
+        let gain_db = gain_db.clamp(-Equalizer::MAX_GAIN_DB, Equalizer::MAX_GAIN_DB);
+        Biquad::new(shape, centre_hz as f32, f32::from(gain_db), sample_rate_hz)
+    }
+
+    /// Every `(frequency code, gain, wide)` setting for one band
+    fn band_options(idx: usize) -> impl Iterator<Item = (u8, i8, bool)> + Clone {
+        let widths: &'static [bool] = if idx == 0 || idx == 4 {
+            &[false]
+        } else {
+            &[false, true]
+        };
+        (0..4u8).flat_map(move |cf| {
+            widths.iter().flat_map(move |&wide| {
+                (-Equalizer::MAX_GAIN_DB..=Equalizer::MAX_GAIN_DB).map(move |gain| (cf, gain, wide))
+            })
+        })
+    }
+
+    /// Find the equalizer setting whose modelled response is closest to a
+    /// target curve.
+    ///
+    /// The target is a list of `(frequency in Hz, gain in dB)` points, and
+    /// closest means the smallest sum of squared errors at those points.
+    ///
+    /// The search adjusts one band at a time, trying every frequency,
+    /// bandwidth and gain for it. When no single band can be improved, it
+    /// tries adjusting neighbouring pairs of bands together. It stops when
+    /// neither helps, or after `max_passes` passes over the bands. This
+    /// takes a while, so it is meant to be run on a host rather than on the
+    /// target.
+    ///
+    /// ```no_run
+    /// use nau88c22::eq::{EqBand, EqPath, EqResponse, Equalizer};
+    /// let mut wanted = Equalizer::new(EqPath::Dac);
+    /// wanted.bands[0] = EqBand::new(80, 5);
+    /// wanted.bands[3] = EqBand::new(3200, -4).wide(true);
+    /// let response = EqResponse::new(&wanted, 48_000.0);
+    /// let mut target = [(0.0, 0.0); 24];
+    /// for (idx, point) in target.iter_mut().enumerate() {
+    ///     let freq = 20.0 * 1.32f32.powi(idx as i32);
+    ///     *point = (freq, response.magnitude_db(freq));
+    /// }
+    /// let fitted = EqResponse::fit(&target, 48_000.0, EqPath::Dac, 10);
+    /// let fitted_response = EqResponse::new(&fitted, 48_000.0);
+    /// for (freq, db) in target {
+    ///     assert!((fitted_response.magnitude_db(freq) - db).abs() < 0.5);
+    /// }
+    /// ```
+    ///
+    /// A target that one band can match exactly is found in a single pass:
+    ///
+    /// ```
+    /// use nau88c22::eq::{EqBand, EqPath, EqResponse, Equalizer};
+    /// let mut wanted = Equalizer::new(EqPath::Dac);
+    /// wanted.bands[0] = EqBand::new(80, 5);
+    /// let response = EqResponse::new(&wanted, 48_000.0);
+    /// let target = [40.0, 160.0, 1000.0].map(|freq| (freq, response.magnitude_db(freq)));
+    /// let fitted = EqResponse::fit(&target, 48_000.0, EqPath::Dac, 1);
+    /// assert_eq!(fitted.bands[0], wanted.bands[0]);
+    /// ```
+    pub fn fit(
+        target: &[(f32, f32)],
+        sample_rate_hz: f32,
+        path: EqPath,
+        max_passes: usize,
+    ) -> Equalizer {
+        let mut eq = Equalizer::new(path);
+        let mut response = EqResponse::new(&eq, sample_rate_hz);
+        let mut best_error = response.error(target);
+        let apply = |eq: &mut Equalizer,
+                     trial: &mut EqResponse,
+                     idx: usize,
+                     (cf, gain_db, wide): (u8, i8, bool)| {
+            trial.bands[idx] = Self::band(idx, cf, gain_db, wide, sample_rate_hz);
+            eq.bands[idx] = EqBand {
+                centre_hz: Equalizer::BAND_FREQUENCIES_HZ[idx][usize::from(cf)],
+                gain_db,
+                wide,
+            };
+        };
+        for _pass in 0..max_passes {
+            let mut improved = false;
+            for idx in 0..eq.bands.len() {
+                for option in Self::band_options(idx) {
+                    let mut trial = response;
+                    let mut trial_eq = eq;
+                    apply(&mut trial_eq, &mut trial, idx, option);
+                    let error = trial.error(target);
+                    if error < best_error {
+                        (best_error, response, eq) = (error, trial, trial_eq);
+                        improved = true;
+                    }
+                }
+            }
+            if !improved {
+                for idx in 0..eq.bands.len() - 1 {
+                    for first in Self::band_options(idx) {
+                        let mut partial = response;
+                        let mut partial_eq = eq;
+                        apply(&mut partial_eq, &mut partial, idx, first);
+                        for second in Self::band_options(idx + 1) {
+                            let mut trial = partial;
+                            let mut trial_eq = partial_eq;
+                            apply(&mut trial_eq, &mut trial, idx + 1, second);
+                            let error = trial.error(target);
+                            if error < best_error {
+                                (best_error, response, eq) = (error, trial, trial_eq);
+                                improved = true;
+                            }
+                        }
+                    }
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        eq
+    }
+
+    /// The sum of squared errors against a target curve
+    fn error(&self, target: &[(f32, f32)]) -> f32 {
+        target
+            .iter()
+            .map(|(freq_hz, db)| {
+                let error = self.magnitude_db(*freq_hz) - db;
+                error * error
+            })
+            .sum()
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up all five equalizer bands, and the path it works on.
+    ///
+    /// Every frequency is moved to the nearest one supported. Returns
+    /// [`Error::OutOfRange`], without writing anything, if any gain is
+    /// outside ±12 dB.
+    pub fn set_equalizer(&mut self, eq: &Equalizer) -> Result<(), Error<I::Error>> {
+        let regs = eq.to_registers().ok_or(Error::OutOfRange)?;
+        self.write_eq1highcutoff(regs.eq1)?;
+        self.write_eq2peak1(regs.eq2)?;
+        self.write_eq3peak2(regs.eq3)?;
+        self.write_eq4peak3(regs.eq4)?;
+        self.write_eq5lowcutoff(regs.eq5)
+    }
+
+    /// Read all five equalizer registers
+    pub fn read_eq_registers(&mut self) -> Result<EqRegisters, Error<I::Error>> {
+        Ok(EqRegisters {
+            eq1: self.read_eq1highcutoff()?,
+            eq2: self.read_eq2peak1()?,
+            eq3: self.read_eq3peak2()?,
+            eq4: self.read_eq4peak3()?,
+            eq5: self.read_eq5lowcutoff()?,
+        })
+    }
+
+    /// Read back the current equalizer setting
+    pub fn equalizer(&mut self) -> Result<Equalizer, Error<I::Error>> {
+        Ok(Equalizer::from_registers(&self.read_eq_registers()?))
+    }
+}
+
+// End of file
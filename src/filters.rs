@@ -0,0 +1,263 @@
+//! The notch and high-pass filters in the NAU88C22 ADC path
+//!
+//! The notch filter is a second-order IIR filter, set by two 14-bit
+//! coefficients split across the four *Notch Filter* registers. Its main use
+//! is removing mains hum.
+//!
+//! The high-pass filter is either a fixed DC blocker, or a second-order
+//! filter with a choice of eight cut-off frequencies which scale with the
+//! sample rate.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::filters::HighPass;
+//! // Remove 50 Hz hum, with a 10 Hz wide notch
+//! codec.set_notch_filter(50.0, 10.0, 48_000.0)?;
+//! // And wind noise
+//! codec.set_adc_highpass(HighPass::Application { cutoff_hz: 150.0 })?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{registers, Codec, Error, Interface};
+
+/// The notch filter coefficients, as written to the registers
+///
+/// From the datasheet:
+///
+/// * `A0 = (1 - tan(wb / 2)) / (1 + tan(wb / 2))`
+/// * `A1 = -(1 + A0) × cos(w0)`
+/// * `NFCA0 = -A0 × 2^13`
+/// * `NFCA1 = -A1 × 2^12`
+///
+/// where `w0 = 2π × centre / fs` and `wb = 2π × bandwidth / fs`.
+///
+/// ```
+/// use nau88c22::filters::NotchCoefficients;
+/// let coeffs = NotchCoefficients::compute(50.0, 10.0, 48_000.0).unwrap();
+/// assert_eq!(coeffs, NotchCoefficients { a0: -8181, a1: 8186 });
+/// // The registers take 14-bit two's complement values
+/// assert_eq!(coeffs.notchfilter1(true).nfca0high(), 0x40);
+/// assert_eq!(coeffs.notchfilter2().nfcaolow(), 0x0B);
+/// // Nothing above Nyquist
+/// assert!(NotchCoefficients::compute(30_000.0, 10.0, 48_000.0).is_none());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NotchCoefficients {
+    /// The `NFCA0` value
+    pub a0: i16,
+    /// The `NFCA1` value
+    pub a1: i16,
+}
+
+impl NotchCoefficients {
+    /// The range of a 14-bit two's complement value
+    const RANGE: core::ops::RangeInclusive<f32> = -8192.0..=8191.0;
+
+    /// Work out the coefficients for a notch at `centre_hz`, `bandwidth_hz`
+    /// wide.
+    ///
+    /// Returns `None` if either frequency is not between zero and half the
+    /// sample rate, or the coefficients do not fit.
+    pub fn compute(
+        centre_hz: f32,
+        bandwidth_hz: f32,
+        sample_rate_hz: f32,
+    ) -> Option<NotchCoefficients> {
+        let nyquist = sample_rate_hz / 2.0;
+        if !(centre_hz > 0.0 && centre_hz < nyquist && bandwidth_hz > 0.0 && bandwidth_hz < nyquist)
+        {
+            return None;
+        }
+        let w0 = 2.0 * core::f32::consts::PI * centre_hz / sample_rate_hz;
+        let wb = 2.0 * core::f32::consts::PI * bandwidth_hz / sample_rate_hz;
+        let tan_half_wb = libm::tanf(wb / 2.0);
+        let a0 = (1.0 - tan_half_wb) / (1.0 + tan_half_wb);
+        let a1 = -(1.0 + a0) * libm::cosf(w0);
+        let nfca0 = libm::roundf(-a0 * 8192.0);
+        let nfca1 = libm::roundf(-a1 * 4096.0);
+        if !Self::RANGE.contains(&nfca0) || !Self::RANGE.contains(&nfca1) {
+            return None;
+        }
+        Some(NotchCoefficients {
+            a0: nfca0 as i16,
+            a1: nfca1 as i16,
+        })
+    }
+
+    /// The *Notch Filter 1* register contents, with the filter enabled or
+    /// not
+    pub fn notchfilter1(&self, enable: bool) -> registers::NotchFilter1 {
+        let mut value = registers::NotchFilter1(0);
+        value.nfcen_set(enable);
+        value.nfca0high_set(Self::high(self.a0));
+        value
+    }
+
+    /// The *Notch Filter 2* register contents
+    pub fn notchfilter2(&self) -> registers::NotchFilter2 {
+        let mut value = registers::NotchFilter2(0);
+        value.nfcaolow_set(Self::low(self.a0));
+        value
+    }
+
+    /// The *Notch Filter 3* register contents
+    pub fn notchfilter3(&self) -> registers::NotchFilter3 {
+        let mut value = registers::NotchFilter3(0);
+        value.nfca1high_set(Self::high(self.a1));
+        value
+    }
+
+    /// The *Notch Filter 4* register contents, with the update bit set
+    pub fn notchfilter4(&self) -> registers::NotchFilter4 {
+        let mut value = registers::NotchFilter4(0);
+        value.nfca1low_set(Self::low(self.a1));
+        value.nfcu4_set(true);
+        value
+    }
+
+    /// The top seven bits of a 14-bit value
+    const fn high(value: i16) -> u8 {
+        ((value >> 7) & 0x7F) as u8
+    }
+
+    /// The bottom seven bits of a 14-bit value
+    const fn low(value: i16) -> u8 {
+        (value & 0x7F) as u8
+    }
+}
+
+/// The settings for the ADC high-pass filter
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HighPass {
+    /// No high-pass filter
+    Off,
+    /// A first-order 3.7 Hz DC blocking filter
+    Audio,
+    /// A second-order filter with a cut-off as close as possible to the one
+    /// given
+    Application {
+        /// The cut-off frequency wanted
+        cutoff_hz: f32,
+    },
+}
+
+impl HighPass {
+    /// The cut-off frequency in audio mode
+    pub const AUDIO_CUTOFF_HZ: f32 = 3.7;
+
+    /// The application mode cut-off frequencies at 8 kHz, 16 kHz or 32 kHz,
+    /// indexed by [`registers::ADCControl::hpf`] value
+    pub const APPLICATION_CUTOFFS_HZ: [f32; 8] =
+        [82.0, 102.0, 131.0, 163.0, 204.0, 261.0, 327.0, 408.0];
+
+    /// The application mode cut-off frequencies at a given sample rate
+    ///
+    /// These scale with the sample rate, within each octave of 8 kHz. So
+    /// 12 kHz, 24 kHz and 48 kHz give frequencies 1.5 times higher than
+    /// 8 kHz, 16 kHz and 32 kHz do.
+    ///
+    /// ```
+    /// use nau88c22::filters::HighPass;
+    /// assert_eq!(HighPass::application_cutoffs_hz(48_000.0)[0], 123.0);
+    /// assert_eq!(HighPass::application_cutoffs_hz(16_000.0)[7], 408.0);
+    /// // Choose a cut-off
+    /// assert_eq!(HighPass::Application { cutoff_hz: 200.0 }.hpf_code(48_000.0), Some(2));
+    /// assert_eq!(HighPass::Audio.hpf_code(48_000.0), None);
+    /// ```
+    pub fn application_cutoffs_hz(sample_rate_hz: f32) -> [f32; 8] {
+        let mut scale = sample_rate_hz / 8000.0;
+        while scale >= 2.0 {
+            scale /= 2.0;
+        }
+        Self::APPLICATION_CUTOFFS_HZ.map(|hz| hz * scale)
+    }
+
+    /// The [`registers::ADCControl::hpf`] code closest to the cut-off
+    /// wanted, in application mode
+    pub fn hpf_code(&self, sample_rate_hz: f32) -> Option<u8> {
+        let HighPass::Application { cutoff_hz } = *self else {
+            return None;
+        };
+        let cutoffs = Self::application_cutoffs_hz(sample_rate_hz);
+        let mut best = 0;
+        for (idx, hz) in cutoffs.iter().enumerate() {
+            if libm::fabsf(hz - cutoff_hz) < libm::fabsf(cutoffs[best] - cutoff_hz) {
+                best = idx;
+            }
+        }
+        Some(best as u8)
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up the ADC high-pass filter.
+    ///
+    /// The sample rate is taken from [`registers::ClockControl2::smplr`], so
+    /// set the clocks first. That field cannot tell 44.1 kHz from 48 kHz, so
+    /// at 44.1 kHz (or 22.05 kHz, or 11.025 kHz) the real application mode
+    /// cut-off will be about 8% lower than the one returned.
+    ///
+    /// Returns the cut-off frequency achieved, or `None` if the filter is
+    /// off.
+    pub fn set_adc_highpass(&mut self, highpass: HighPass) -> Result<Option<f32>, Error<I::Error>> {
+        let sample_rate_hz = self.sample_rate_hint_hz()? as f32;
+        let code = highpass.hpf_code(sample_rate_hz);
+        self.modify_adccontrol(|mut w| {
+            w.hpfen_set(highpass != HighPass::Off);
+            w.hpfam_set(code.is_some());
+            if let Some(code) = code {
+                w.hpf_set(code);
+            }
+            w
+        })?;
+        Ok(match highpass {
+            HighPass::Off => None,
+            HighPass::Audio => Some(HighPass::AUDIO_CUTOFF_HZ),
+            HighPass::Application { .. } => {
+                code.map(|code| HighPass::application_cutoffs_hz(sample_rate_hz)[usize::from(code)])
+            }
+        })
+    }
+
+    /// Set up and enable the notch filter.
+    ///
+    /// The four registers are written with only the last update bit set, so
+    /// the new coefficients all take effect together. Returns the
+    /// coefficients used, or [`Error::OutOfRange`] if the frequencies are
+    /// impossible.
+    pub fn set_notch_filter(
+        &mut self,
+        centre_hz: f32,
+        bandwidth_hz: f32,
+        sample_rate_hz: f32,
+    ) -> Result<NotchCoefficients, Error<I::Error>> {
+        let coeffs = NotchCoefficients::compute(centre_hz, bandwidth_hz, sample_rate_hz)
+            .ok_or(Error::OutOfRange)?;
+        self.write_notchfilter1(coeffs.notchfilter1(true))?;
+        self.write_notchfilter2(coeffs.notchfilter2())?;
+        self.write_notchfilter3(coeffs.notchfilter3())?;
+        self.write_notchfilter4(coeffs.notchfilter4())?;
+        Ok(coeffs)
+    }
+
+    /// Disable the notch filter
+    pub fn disable_notch_filter(&mut self) -> Result<(), Error<I::Error>> {
+        self.modify_notchfilter1(|mut w| {
+            w.nfcen_set(false);
+            w.nfcu1_set(true);
+            w
+        })
+    }
+}
+
+// End of file
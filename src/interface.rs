@@ -0,0 +1,195 @@
+//! Control interfaces for the NAU88C22
+//!
+//! The CODEC can be controlled over I²C (MODE pin low) or over 3-wire or
+//! 4-wire SPI (MODE pin high). Both carry the same 16-bit frame: a 7-bit
+//! register address followed by a 9-bit register value.
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use embedded_hal::{
+    i2c::{I2c, SevenBitAddress},
+    spi::{Operation, SpiDevice},
+};
+
+use crate::{Error, Register};
+
+/// Something that can read and write the nine-bit registers on the CODEC
+///
+/// Every `embedded_hal::i2c::I2c` implementation is an `Interface`, so you
+/// can give your I²C bus straight to [`Codec::new`](crate::Codec::new). For
+/// SPI, wrap your SPI device in a [`SpiInterface`].
+pub trait Interface {
+    /// The error type produced by the underlying bus
+    type Error;
+
+    /// Read a nine-bit register from the chip.
+    ///
+    /// The value comes back in the lowest 9 bits of a `u16`.
+    fn read_register(&mut self, register: Register) -> Result<u16, Error<Self::Error>>;
+
+    /// Write a nine-bit register to the chip.
+    ///
+    /// The value should be given in the lowest 9 bits of a `u16`.
+    fn write_register(&mut self, register: Register, value: u16) -> Result<(), Error<Self::Error>>;
+}
+
+/// Our I2C device address
+pub(crate) const DEVICE_ADDR: SevenBitAddress = 0b0011010;
+
+/// Build the two byte frame used by both I²C and SPI writes
+pub(crate) fn write_frame(register: Register, value: u16) -> [u8; 2] {
+    [((register as u8) * 2) | (value >> 8) as u8, value as u8]
+}
+
+/// Unpack the two bytes sent back by the chip on a register read
+pub(crate) fn read_frame(buffer: [u8; 2]) -> u16 {
+    let mut result = ((buffer[0] as u16) & 1) << 8;
+    result |= buffer[1] as u16;
+    result
+}
+
+impl<T> Interface for T
+where
+    T: I2c,
+{
+    type Error = T::Error;
+
+    fn read_register(&mut self, register: Register) -> Result<u16, Error<Self::Error>> {
+        let mut buffer = [0u8; 2];
+        self.write_read(DEVICE_ADDR, &[(register as u8) * 2], &mut buffer)
+            .map_err(Error::I2c)?;
+        Ok(read_frame(buffer))
+    }
+
+    fn write_register(&mut self, register: Register, value: u16) -> Result<(), Error<Self::Error>> {
+        self.write(DEVICE_ADDR, &write_frame(register, value))
+            .map_err(Error::I2c)
+    }
+}
+
+/// The SPI control modes supported by the chip
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiMode {
+    /// 3-wire mode (CSB, SCLK, SDIO). Registers can be written but not read.
+    ThreeWire,
+    /// 4-wire mode (CSB, SCLK, SDIO, GPIO1 as data out). Registers can be
+    /// written and read.
+    ///
+    /// The chip only drives read data once 4-wire mode has been enabled,
+    /// either with [`ClockControl2::fourwirecie`](crate::registers::ClockControl2::fourwirecie)
+    /// or [`MiscControls::fwspiena`](crate::registers::MiscControls::fwspiena).
+    /// Note that [`Codec::reset`](crate::Codec::reset) clears both.
+    FourWire,
+}
+
+/// Drives the CODEC control port over SPI
+///
+/// Every write is a single 16-bit frame, latched on the rising edge of CSB.
+/// In 4-wire mode a read clocks out the register address byte and then
+/// clocks the nine-bit value back in over the next 16 bits.
+///
+/// ```
+/// # use embedded_hal::spi::{self as halspi, Operation, SpiDevice, ErrorKind};
+/// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// # pub enum Error { }
+/// # impl halspi::Error for Error {
+/// #     fn kind(&self) -> ErrorKind {
+/// #         ErrorKind::Other
+/// #     }
+/// # }
+/// /// Records the bytes written, and answers reads with `0x01A`
+/// #[derive(Default)]
+/// struct MockSpi {
+///     written: [u8; 4],
+///     len: usize,
+/// }
+/// # impl halspi::ErrorType for MockSpi {
+/// #     type Error = Error;
+/// # }
+/// impl SpiDevice for MockSpi {
+///     fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Error> {
+///         for op in operations {
+///             match op {
+///                 Operation::Write(data) => {
+///                     self.written[self.len..self.len + data.len()].copy_from_slice(data);
+///                     self.len += data.len();
+///                 }
+///                 Operation::Read(buffer) => buffer.copy_from_slice(&[0x00, 0x1A]),
+///                 _ => unimplemented!(),
+///             }
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// use nau88c22::{Codec, SpiInterface, SpiMode, Register};
+/// let mut codec = Codec::new(SpiInterface::new(MockSpi::default(), SpiMode::FourWire));
+/// codec.write_register(Register::LHPVolume, 0x139).unwrap();
+/// assert!(codec.check_device_id().is_ok());
+/// let spi = codec.release().release();
+/// // 0x34 << 1 | 1, then 0x39; then the read address 0x3F << 1
+/// assert_eq!(&spi.written[..3], &[0x69, 0x39, 0x7E]);
+///
+/// let mut codec = Codec::new(SpiInterface::new(MockSpi::default(), SpiMode::ThreeWire));
+/// assert_eq!(codec.check_device_id(), Err(nau88c22::Error::WriteOnlyInterface));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpiInterface<S> {
+    spi: S,
+    mode: SpiMode,
+}
+
+impl<S> SpiInterface<S>
+where
+    S: SpiDevice,
+{
+    /// Create a new SPI interface.
+    ///
+    /// The SPI device should be configured for SPI mode 0, MSB first, and
+    /// must handle CSB for each transaction.
+    pub const fn new(spi: S, mode: SpiMode) -> SpiInterface<S> {
+        SpiInterface { spi, mode }
+    }
+
+    /// Which SPI mode we are using
+    pub fn mode(&self) -> SpiMode {
+        self.mode
+    }
+
+    /// Give back the SPI device
+    pub fn release(self) -> S {
+        self.spi
+    }
+}
+
+impl<S> Interface for SpiInterface<S>
+where
+    S: SpiDevice,
+{
+    type Error = S::Error;
+
+    fn read_register(&mut self, register: Register) -> Result<u16, Error<Self::Error>> {
+        if self.mode == SpiMode::ThreeWire {
+            return Err(Error::WriteOnlyInterface);
+        }
+        let mut buffer = [0u8; 2];
+        self.spi
+            .transaction(&mut [
+                Operation::Write(&[(register as u8) * 2]),
+                Operation::Read(&mut buffer),
+            ])
+            .map_err(Error::Spi)?;
+        Ok(read_frame(buffer))
+    }
+
+    fn write_register(&mut self, register: Register, value: u16) -> Result<(), Error<Self::Error>> {
+        self.spi
+            .write(&write_frame(register, value))
+            .map_err(Error::Spi)
+    }
+}
+
+// End of file
@@ -0,0 +1,323 @@
+//! Headphone jack detection for the NAU88C22
+//!
+//! The CODEC can watch a jack switch on one of its GPIO pins, and turn
+//! output drivers on and off depending on whether a plug is inserted. It
+//! cannot report the jack state over the control interface, so to find out
+//! about insertions the MCU watches the same line - see [`JackMonitor`].
+//!
+//! ```rust,no_run
+//! # fn example<I, P>(codec: &mut nau88c22::Codec<I>, jack_pin: &mut P) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface, P: embedded_hal::digital::InputPin {
+//! use nau88c22::jack::{JackDetect, JackEvent, JackMonitor, JackOutput, JackPin};
+//! // Headphones when a plug is in, speakers when it is out
+//! let jack = JackDetect::new(JackPin::Gpio2)
+//!     .on_insertion(JackOutput::Headphones)
+//!     .on_removal(JackOutput::Speakers);
+//! codec.configure_jack_detect(&jack)?;
+//! let mut monitor = JackMonitor::new(&jack, 5);
+//! loop {
+//!     match monitor.poll(jack_pin) {
+//!         Ok(Some(JackEvent::Inserted)) => { /* show the headphone icon */ }
+//!         Ok(Some(JackEvent::Removed)) => { /* hide it */ }
+//!         _ => {}
+//!     }
+//! }
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use embedded_hal::digital::InputPin;
+
+use crate::{registers, Codec, Error, Interface};
+
+/// The pins which can sense the jack switch
+/// ([`registers::JackDetect1::jckdio`])
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JackPin {
+    /// CSB/GPIO1 (default). Only usable in I²C or 2-wire mode.
+    Gpio1,
+    /// GPIO2
+    Gpio2,
+    /// GPIO3
+    Gpio3,
+}
+
+/// The output drivers jack detection can switch
+/// ([`registers::JackDetect2`])
+///
+/// Left and right outputs are switched as a pair.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JackOutput {
+    /// The LHP and RHP headphone drivers
+    Headphones,
+    /// The LSPKOUT and RSPKOUT loudspeaker drivers
+    Speakers,
+    /// The AUXOUT2 driver
+    Aux2,
+    /// The AUXOUT1 driver
+    Aux1,
+}
+
+impl JackOutput {
+    /// This output's bit in a [`registers::JackDetect2`] field
+    const fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// Describes the jack detection we want
+///
+/// By default the jack switch pulls the pin high when a plug is inserted,
+/// no outputs are switched, and the internal bias amplifiers are not
+/// switched either.
+///
+/// Once jack detection is enabled, an output driver only runs in the jack
+/// states it is listed for - so list an output in both states if it should
+/// always be on.
+///
+/// ```
+/// use nau88c22::jack::{JackDetect, JackOutput, JackPin};
+/// let jack = JackDetect::new(JackPin::Gpio3)
+///     .inserted_when_high(false)
+///     .on_insertion(JackOutput::Headphones)
+///     .on_removal(JackOutput::Speakers)
+///     .on_removal(JackOutput::Aux1)
+///     .bias_on_insertion(true);
+/// let jackdetect1 = jack.jackdetect1();
+/// assert!(jackdetect1.jacden());
+/// assert_eq!(jackdetect1.jckdio(), 2);
+/// // Bias amplifiers on when the pin is low
+/// assert_eq!(jackdetect1.jckmiden(), 1);
+/// let jackdetect2 = jack.jackdetect2();
+/// // A low pin means a plug is inserted
+/// assert_eq!(jackdetect2.jckdoen0_lrhp(), 0b0001);
+/// assert_eq!(jackdetect2.jckdoen1_lrhp(), 0b1010);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct JackDetect {
+    pin: JackPin,
+    inserted_when_high: bool,
+    inserted_outputs: u8,
+    removed_outputs: u8,
+    bias_on_insertion: bool,
+}
+
+impl JackDetect {
+    /// Describe jack detection on the given pin
+    pub const fn new(pin: JackPin) -> JackDetect {
+        JackDetect {
+            pin,
+            inserted_when_high: true,
+            inserted_outputs: 0,
+            removed_outputs: 0,
+            bias_on_insertion: false,
+        }
+    }
+
+    /// Set whether the pin is high (`true`) or low (`false`) when a plug is
+    /// inserted
+    pub const fn inserted_when_high(self, inserted_when_high: bool) -> JackDetect {
+        JackDetect {
+            inserted_when_high,
+            ..self
+        }
+    }
+
+    /// Enable an output while a plug is inserted
+    pub const fn on_insertion(self, output: JackOutput) -> JackDetect {
+        JackDetect {
+            inserted_outputs: self.inserted_outputs | output.bit(),
+            ..self
+        }
+    }
+
+    /// Enable an output while no plug is inserted
+    pub const fn on_removal(self, output: JackOutput) -> JackDetect {
+        JackDetect {
+            removed_outputs: self.removed_outputs | output.bit(),
+            ..self
+        }
+    }
+
+    /// Enable the internal bias amplifiers while a plug is inserted
+    /// ([`registers::JackDetect1::jckmiden`])
+    pub const fn bias_on_insertion(self, bias_on_insertion: bool) -> JackDetect {
+        JackDetect {
+            bias_on_insertion,
+            ..self
+        }
+    }
+
+    /// The pin the jack switch is on
+    pub const fn pin(&self) -> JackPin {
+        self.pin
+    }
+
+    /// Does this pin level mean a plug is inserted?
+    pub const fn is_inserted(&self, pin_high: bool) -> bool {
+        pin_high == self.inserted_when_high
+    }
+
+    /// The *Jack Detect 1* register contents, with jack detection enabled
+    pub fn jackdetect1(&self) -> registers::JackDetect1 {
+        let mut value = registers::JackDetect1(0);
+        value.jckmiden_set(match (self.bias_on_insertion, self.inserted_when_high) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => 2,
+        });
+        value.jacden_set(true);
+        value.jckdio_set(self.pin as u8);
+        value
+    }
+
+    /// The *Jack Detect 2* register contents
+    pub fn jackdetect2(&self) -> registers::JackDetect2 {
+        let (high, low) = if self.inserted_when_high {
+            (self.inserted_outputs, self.removed_outputs)
+        } else {
+            (self.removed_outputs, self.inserted_outputs)
+        };
+        let mut value = registers::JackDetect2(0);
+        value.jckdoen1_set(high);
+        value.jckdoen0_set(low);
+        value
+    }
+}
+
+/// A change in the jack state
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JackEvent {
+    /// A plug has been inserted
+    Inserted,
+    /// The plug has been removed
+    Removed,
+}
+
+/// Watches the jack switch line and reports insertions and removals
+///
+/// Give it a reading of the line at regular intervals - either with
+/// [`JackMonitor::poll`], or with [`JackMonitor::update`] if you read the
+/// line yourself (e.g. after a pin-change interrupt). A new state is only
+/// reported once the same level has been read `debounce_samples` times in a
+/// row. The first stable state is reported as an event too, so you find out
+/// if a plug was already inserted at start-up.
+///
+/// ```
+/// use nau88c22::jack::{JackDetect, JackEvent, JackMonitor, JackPin};
+/// let mut monitor = JackMonitor::new(&JackDetect::new(JackPin::Gpio1), 3);
+/// assert_eq!(monitor.update(false), None);
+/// assert_eq!(monitor.update(false), None);
+/// assert_eq!(monitor.update(false), Some(JackEvent::Removed));
+/// // Contact bounce is ignored
+/// assert_eq!(monitor.update(true), None);
+/// assert_eq!(monitor.update(false), None);
+/// assert_eq!(monitor.update(true), None);
+/// assert_eq!(monitor.update(true), None);
+/// assert_eq!(monitor.update(true), Some(JackEvent::Inserted));
+/// assert_eq!(monitor.is_inserted(), Some(true));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct JackMonitor {
+    inserted_when_high: bool,
+    debounce_samples: u8,
+    inserted: Option<bool>,
+    candidate: bool,
+    count: u8,
+}
+
+impl JackMonitor {
+    /// Watch the jack described by `jack`.
+    ///
+    /// A `debounce_samples` of zero is treated as one.
+    pub const fn new(jack: &JackDetect, debounce_samples: u8) -> JackMonitor {
+        JackMonitor {
+            inserted_when_high: jack.inserted_when_high,
+            debounce_samples,
+            inserted: None,
+            candidate: false,
+            count: 0,
+        }
+    }
+
+    /// Whether a plug is inserted, or `None` if the line hasn't settled yet
+    pub const fn is_inserted(&self) -> Option<bool> {
+        self.inserted
+    }
+
+    /// Take a reading of the line, `true` being high.
+    ///
+    /// Returns an event if the jack state has changed.
+    pub fn update(&mut self, pin_high: bool) -> Option<JackEvent> {
+        let inserted = pin_high == self.inserted_when_high;
+        if self.inserted == Some(inserted) {
+            self.count = 0;
+            return None;
+        }
+        if self.count == 0 || self.candidate != inserted {
+            self.candidate = inserted;
+            self.count = 1;
+        } else {
+            self.count = self.count.saturating_add(1);
+        }
+        if self.count < self.debounce_samples {
+            return None;
+        }
+        self.inserted = Some(inserted);
+        self.count = 0;
+        Some(if inserted {
+            JackEvent::Inserted
+        } else {
+            JackEvent::Removed
+        })
+    }
+
+    /// Read the line from an MCU input pin, and take it as a reading.
+    ///
+    /// See [`JackMonitor::update`].
+    pub fn poll<P>(&mut self, pin: &mut P) -> Result<Option<JackEvent>, P::Error>
+    where
+        P: InputPin,
+    {
+        let pin_high = pin.is_high()?;
+        Ok(self.update(pin_high))
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up and enable jack detection.
+    ///
+    /// If the jack switch is on GPIO1, that pin is made an input.
+    pub fn configure_jack_detect(&mut self, jack: &JackDetect) -> Result<(), Error<I::Error>> {
+        if jack.pin == JackPin::Gpio1 {
+            self.modify_gpio(|mut w| {
+                w.gpio1sel_set(registers::Gpio1Selection::Input as u8);
+                w
+            })?;
+        }
+        self.write_jackdetect2(jack.jackdetect2())?;
+        self.write_jackdetect1(jack.jackdetect1())
+    }
+
+    /// Disable jack detection, so the outputs no longer depend on the jack
+    /// state
+    pub fn disable_jack_detect(&mut self) -> Result<(), Error<I::Error>> {
+        self.modify_jackdetect1(|mut w| {
+            w.jacden_set(false);
+            w.jckmiden_set(0);
+            w
+        })
+    }
+}
+
+// End of file
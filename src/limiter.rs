@@ -0,0 +1,280 @@
+//! The DAC digital limiter in the NAU88C22
+//!
+//! The limiter reduces the DAC gain when the output goes above a threshold,
+//! and can add up to 12 dB of automatic boost to quieter signals. With the
+//! limiter disabled, the boost is applied as a fixed make-up gain.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use core::time::Duration;
+//! use nau88c22::limiter::DacLimiter;
+//! codec.configure_dac_limiter(&DacLimiter {
+//!     threshold_db: -3.0,
+//!     boost_db: 6.0,
+//!     attack: Duration::from_micros(500),
+//!     decay: Duration::from_millis(70),
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::time::Duration;
+
+use crate::{util, volume::GainRange, Codec, Error, Interface};
+
+/// Describes the DAC limiter behaviour we want
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DacLimiter {
+    /// The output level the limiter holds the signal below, from -6 dB to
+    /// -1 dB relative to full scale
+    pub threshold_db: f32,
+    /// The most gain the limiter may add, from 0 dB to +12 dB
+    pub boost_db: f32,
+    /// How quickly the gain is reduced
+    pub attack: Duration,
+    /// How quickly the gain recovers
+    pub decay: Duration,
+}
+
+impl DacLimiter {
+    /// The sample rate the time tables are given for
+    pub const TABLE_SAMPLE_RATE_HZ: f32 = 44_100.0;
+
+    /// The thresholds, in dB, indexed by
+    /// [`registers::DACLimiter2::daclimthl`] value
+    ///
+    /// [`registers::DACLimiter2::daclimthl`]: crate::registers::DACLimiter2::daclimthl
+    pub const THRESHOLDS_DB: [f32; 6] = [-1.0, -2.0, -3.0, -4.0, -5.0, -6.0];
+
+    /// The boost gains ([`registers::DACLimiter2::daclimbst`])
+    ///
+    /// [`registers::DACLimiter2::daclimbst`]: crate::registers::DACLimiter2::daclimbst
+    pub const BOOST_RANGE: GainRange = GainRange {
+        min_code: 0,
+        max_code: 12,
+        min_db: 0.0,
+        step_db: 1.0,
+    };
+
+    /// The attack times at 44.1 kHz, in µs, indexed by
+    /// [`registers::DACLimiter1::daclimatk`] value
+    ///
+    /// [`registers::DACLimiter1::daclimatk`]: crate::registers::DACLimiter1::daclimatk
+    pub const ATTACK_TIMES_US: [u32; 12] = [
+        68, 136, 272, 544, 1_090, 2_180, 4_360, 8_720, 17_400, 34_800, 69_600, 139_000,
+    ];
+
+    /// The decay times at 44.1 kHz, in µs, indexed by
+    /// [`registers::DACLimiter1::daclimdcy`] value
+    ///
+    /// [`registers::DACLimiter1::daclimdcy`]: crate::registers::DACLimiter1::daclimdcy
+    pub const DECAY_TIMES_US: [u32; 12] = [
+        544, 1_090, 2_180, 4_360, 8_720, 17_400, 34_800, 69_600, 139_000, 278_000, 566_000,
+        1_130_000,
+    ];
+
+    /// Work out the register settings which get closest to the behaviour
+    /// asked for, at the given sample rate.
+    ///
+    /// The limiter's times are inversely proportional to the sample rate.
+    /// They are rounded to the nearest one on offer (on a log scale), and
+    /// clamped to the shortest and longest. Returns `None` if the threshold
+    /// or boost is out of range, or the sample rate is not positive.
+    ///
+    /// ```
+    /// use core::time::Duration;
+    /// use nau88c22::limiter::DacLimiter;
+    /// let limiter = DacLimiter {
+    ///     threshold_db: -3.0,
+    ///     boost_db: 6.0,
+    ///     attack: Duration::from_micros(272),
+    ///     decay: Duration::from_micros(4_360),
+    /// };
+    /// let settings = limiter.compute(44_100.0).unwrap();
+    /// assert_eq!((settings.daclimthl, settings.daclimbst), (2, 6));
+    /// assert_eq!((settings.daclimatk, settings.daclimdcy), (2, 3));
+    /// // Times are twice as long at half the sample rate
+    /// let settings = limiter.compute(22_050.0).unwrap();
+    /// assert_eq!((settings.daclimatk, settings.daclimdcy), (1, 2));
+    /// assert_eq!(settings.attack, Duration::from_micros(272));
+    /// // No more than 12 dB of boost
+    /// assert!(DacLimiter { boost_db: 13.0, ..limiter }.compute(44_100.0).is_none());
+    /// // Thresholds from -6 dB to -1 dB
+    /// assert_eq!(DacLimiter { threshold_db: -6.4, ..limiter }.compute(44_100.0).unwrap().daclimthl, 5);
+    /// assert!(DacLimiter { threshold_db: -6.6, ..limiter }.compute(44_100.0).is_none());
+    /// assert!(DacLimiter { threshold_db: -0.4, ..limiter }.compute(44_100.0).is_none());
+    /// ```
+    pub fn compute(&self, sample_rate_hz: f32) -> Option<DacLimiterSettings> {
+        if sample_rate_hz.is_nan() || sample_rate_hz <= 0.0 {
+            return None;
+        }
+        let daclimthl = util::level_code(&Self::THRESHOLDS_DB, self.threshold_db)?;
+        let daclimbst = Self::BOOST_RANGE.code_for(self.boost_db)?;
+        // Convert to the equivalent time at 44.1 kHz
+        let scale = sample_rate_hz / Self::TABLE_SAMPLE_RATE_HZ;
+        let daclimatk = util::time_code(
+            &Self::ATTACK_TIMES_US,
+            self.attack.as_micros() as f32 * scale,
+        );
+        let daclimdcy =
+            util::time_code(&Self::DECAY_TIMES_US, self.decay.as_micros() as f32 * scale);
+        let time = |table: &[u32; 12], code: u8| {
+            Duration::from_micros(libm::roundf(table[usize::from(code)] as f32 / scale) as u64)
+        };
+        Some(DacLimiterSettings {
+            daclimthl,
+            daclimbst,
+            daclimatk,
+            daclimdcy,
+            attack: time(&Self::ATTACK_TIMES_US, daclimatk),
+            decay: time(&Self::DECAY_TIMES_US, daclimdcy),
+        })
+    }
+}
+
+/// The register settings for a [`DacLimiter`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DacLimiterSettings {
+    /// The threshold code ([`registers::DACLimiter2::daclimthl`])
+    ///
+    /// [`registers::DACLimiter2::daclimthl`]: crate::registers::DACLimiter2::daclimthl
+    pub daclimthl: u8,
+    /// The boost code ([`registers::DACLimiter2::daclimbst`])
+    ///
+    /// [`registers::DACLimiter2::daclimbst`]: crate::registers::DACLimiter2::daclimbst
+    pub daclimbst: u8,
+    /// The attack time code ([`registers::DACLimiter1::daclimatk`])
+    ///
+    /// [`registers::DACLimiter1::daclimatk`]: crate::registers::DACLimiter1::daclimatk
+    pub daclimatk: u8,
+    /// The decay time code ([`registers::DACLimiter1::daclimdcy`])
+    ///
+    /// [`registers::DACLimiter1::daclimdcy`]: crate::registers::DACLimiter1::daclimdcy
+    pub daclimdcy: u8,
+    /// The attack time these settings give
+    pub attack: Duration,
+    /// The decay time these settings give
+    pub decay: Duration,
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up and enable the DAC limiter.
+    ///
+    /// The sample rate is taken from [`registers::ClockControl2::smplr`], so
+    /// set the clocks first. That field cannot tell 44.1 kHz from 48 kHz, so
+    /// at 44.1 kHz (or 22.05 kHz, or 11.025 kHz) the real times will be
+    /// about 9% longer than the ones returned.
+    ///
+    /// Returns the settings used, or [`Error::OutOfRange`] if the threshold
+    /// or boost is out of range.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Records every register write, and reads every register as zero
+    /// #[derive(Default)]
+    /// struct MockI2c {
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(_), Operation::Read(buffer)] => buffer.fill(0),
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// use core::time::Duration;
+    /// use nau88c22::limiter::DacLimiter;
+    /// let mut codec = nau88c22::Codec::new(MockI2c::default());
+    /// let limiter = DacLimiter {
+    ///     threshold_db: -6.0,
+    ///     boost_db: 0.0,
+    ///     attack: Duration::from_micros(500),
+    ///     decay: Duration::from_millis(70),
+    /// };
+    /// assert_eq!(codec.configure_dac_limiter(&limiter).unwrap().daclimthl, 5);
+    /// let limiter = DacLimiter { threshold_db: -1.0, ..limiter };
+    /// assert_eq!(codec.configure_dac_limiter(&limiter).unwrap().daclimthl, 0);
+    /// let writes = codec.release().writes.len();
+    /// // Thresholds beyond either end are rejected before anything is written
+    /// let mut codec = nau88c22::Codec::new(MockI2c::default());
+    /// let limiter = DacLimiter { threshold_db: -7.0, ..limiter };
+    /// assert_eq!(codec.configure_dac_limiter(&limiter), Err(nau88c22::Error::OutOfRange));
+    /// let limiter = DacLimiter { threshold_db: 0.0, ..limiter };
+    /// assert_eq!(codec.configure_dac_limiter(&limiter), Err(nau88c22::Error::OutOfRange));
+    /// assert_eq!((writes, codec.release().writes.len()), (4, 0));
+    /// ```
+    ///
+    /// [`registers::ClockControl2::smplr`]: crate::registers::ClockControl2::smplr
+    pub fn configure_dac_limiter(
+        &mut self,
+        limiter: &DacLimiter,
+    ) -> Result<DacLimiterSettings, Error<I::Error>> {
+        let sample_rate_hz = self.sample_rate_hint_hz()? as f32;
+        let settings = limiter.compute(sample_rate_hz).ok_or(Error::OutOfRange)?;
+        self.modify_daclimiter2(|mut w| {
+            w.daclimthl_set(settings.daclimthl);
+            w.daclimbst_set(settings.daclimbst);
+            w
+        })?;
+        self.modify_daclimiter1(|mut w| {
+            w.daclimen_set(true);
+            w.daclimdcy_set(settings.daclimdcy);
+            w.daclimatk_set(settings.daclimatk);
+            w
+        })?;
+        Ok(settings)
+    }
+
+    /// Disable the DAC limiter, and use its boost as a fixed make-up gain
+    /// of 0 dB to +12 dB in 1 dB steps.
+    ///
+    /// Returns the gain set, or [`Error::OutOfRange`] if the gain is out of
+    /// range.
+    pub fn set_dac_makeup_gain_db(&mut self, boost_db: f32) -> Result<f32, Error<I::Error>> {
+        let daclimbst = DacLimiter::BOOST_RANGE
+            .code_for(boost_db)
+            .ok_or(Error::OutOfRange)?;
+        self.modify_daclimiter1(|mut w| {
+            w.daclimen_set(false);
+            w
+        })?;
+        self.modify_daclimiter2(|mut w| {
+            w.daclimbst_set(daclimbst);
+            w
+        })?;
+        Ok(DacLimiter::BOOST_RANGE.decibels(daclimbst).db())
+    }
+
+    /// Disable the DAC limiter, and its boost
+    pub fn disable_dac_limiter(&mut self) -> Result<(), Error<I::Error>> {
+        self.set_dac_makeup_gain_db(0.0).map(|_| ())
+    }
+}
+
+// End of file
@@ -0,0 +1,130 @@
+//! Macros for generating the per-register boilerplate
+//!
+//! [`Codec`](crate::Codec) and `AsyncCodec` both get a `read_xxx`,
+//! `write_xxx` and `modify_xxx` method for every register, generated from the
+//! one list here so the two cannot drift apart. Every register type also
+//! gets a [`Default`] implementation.
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+/// Generate the accessor methods for one register.
+///
+/// The first two arguments are `[]` and `[]` for blocking methods, or
+/// `[async]` and `[.await]` for async methods. The methods call the
+/// `read_register`, `write_register` and `modify_register` methods of the
+/// surrounding `impl` block.
+macro_rules! register_accessor {
+    ([$($async:tt)*] [$($await:tt)*] $name:literal, $reg:ident, $read:ident, $write:ident, $modify:ident) => {
+        #[doc = concat!("Read the *", $name, " register* contents")]
+        pub $($async)* fn $read(&mut self) -> Result<registers::$reg, Error<I::Error>> {
+            let value = self.read_register(Register::$reg)$($await)*?;
+            Ok(registers::$reg(value))
+        }
+
+        #[doc = concat!("Write the *", $name, " register* contents")]
+        pub $($async)* fn $write(&mut self, value: registers::$reg) -> Result<(), Error<I::Error>> {
+            self.write_register(Register::$reg, value.0)$($await)*
+        }
+
+        #[doc = concat!("Modify the *", $name, " register* contents")]
+        pub $($async)* fn $modify<F>(&mut self, f: F) -> Result<(), Error<I::Error>>
+        where
+            F: FnOnce(registers::$reg) -> registers::$reg,
+        {
+            self.modify_register(Register::$reg, |value| f(registers::$reg(value)).0)$($await)*
+        }
+    };
+}
+
+/// Generate the accessor methods for every register.
+///
+/// Takes the same first two arguments as `register_accessor!`.
+macro_rules! register_accessors {
+    ($asyncness:tt $await:tt) => {
+        register_accessor!($asyncness $await "Power Management 1", PowerManagement1, read_powermanagement1, write_powermanagement1, modify_powermanagement1);
+        register_accessor!($asyncness $await "Power Management 2", PowerManagement2, read_powermanagement2, write_powermanagement2, modify_powermanagement2);
+        register_accessor!($asyncness $await "Power Management 3", PowerManagement3, read_powermanagement3, write_powermanagement3, modify_powermanagement3);
+        register_accessor!($asyncness $await "Audio Interface", AudioInterface, read_audiointerface, write_audiointerface, modify_audiointerface);
+        register_accessor!($asyncness $await "Companding", Companding, read_companding, write_companding, modify_companding);
+        register_accessor!($asyncness $await "Clock Control 1", ClockControl1, read_clockcontrol1, write_clockcontrol1, modify_clockcontrol1);
+        register_accessor!($asyncness $await "Clock Control 2", ClockControl2, read_clockcontrol2, write_clockcontrol2, modify_clockcontrol2);
+        register_accessor!($asyncness $await "GPIO", GPIO, read_gpio, write_gpio, modify_gpio);
+        register_accessor!($asyncness $await "Jack Detect 1", JackDetect1, read_jackdetect1, write_jackdetect1, modify_jackdetect1);
+        register_accessor!($asyncness $await "DAC Control", DACControl, read_daccontrol, write_daccontrol, modify_daccontrol);
+        register_accessor!($asyncness $await "Left DAC Volume", LeftDACVolume, read_leftdacvolume, write_leftdacvolume, modify_leftdacvolume);
+        register_accessor!($asyncness $await "Right DAC Volume", RightDACVolume, read_rightdacvolume, write_rightdacvolume, modify_rightdacvolume);
+        register_accessor!($asyncness $await "Jack Detect 2", JackDetect2, read_jackdetect2, write_jackdetect2, modify_jackdetect2);
+        register_accessor!($asyncness $await "ADC Control", ADCControl, read_adccontrol, write_adccontrol, modify_adccontrol);
+        register_accessor!($asyncness $await "Left ADC Volume", LeftADCVolume, read_leftadcvolume, write_leftadcvolume, modify_leftadcvolume);
+        register_accessor!($asyncness $await "Right ADC Volume", RightADCVolume, read_rightadcvolume, write_rightadcvolume, modify_rightadcvolume);
+        register_accessor!($asyncness $await "EQ1-high cutoff", EQ1HighCutoff, read_eq1highcutoff, write_eq1highcutoff, modify_eq1highcutoff);
+        register_accessor!($asyncness $await "EQ2-peak 1", EQ2Peak1, read_eq2peak1, write_eq2peak1, modify_eq2peak1);
+        register_accessor!($asyncness $await "EQ3-peak 2", EQ3Peak2, read_eq3peak2, write_eq3peak2, modify_eq3peak2);
+        register_accessor!($asyncness $await "EQ4-peak 3", EQ4Peak3, read_eq4peak3, write_eq4peak3, modify_eq4peak3);
+        register_accessor!($asyncness $await "EQ5-low cutoff", EQ5LowCutoff, read_eq5lowcutoff, write_eq5lowcutoff, modify_eq5lowcutoff);
+        register_accessor!($asyncness $await "DAC Limiter 1", DACLimiter1, read_daclimiter1, write_daclimiter1, modify_daclimiter1);
+        register_accessor!($asyncness $await "DAC Limiter 2", DACLimiter2, read_daclimiter2, write_daclimiter2, modify_daclimiter2);
+        register_accessor!($asyncness $await "Notch Filter 1", NotchFilter1, read_notchfilter1, write_notchfilter1, modify_notchfilter1);
+        register_accessor!($asyncness $await "Notch Filter 2", NotchFilter2, read_notchfilter2, write_notchfilter2, modify_notchfilter2);
+        register_accessor!($asyncness $await "Notch Filter 3", NotchFilter3, read_notchfilter3, write_notchfilter3, modify_notchfilter3);
+        register_accessor!($asyncness $await "Notch Filter 4", NotchFilter4, read_notchfilter4, write_notchfilter4, modify_notchfilter4);
+        register_accessor!($asyncness $await "ALC Control 1", ALCControl1, read_alccontrol1, write_alccontrol1, modify_alccontrol1);
+        register_accessor!($asyncness $await "ALC Control 2", ALCControl2, read_alccontrol2, write_alccontrol2, modify_alccontrol2);
+        register_accessor!($asyncness $await "ALC Control 3", ALCControl3, read_alccontrol3, write_alccontrol3, modify_alccontrol3);
+        register_accessor!($asyncness $await "Noise Gate", NoiseGate, read_noisegate, write_noisegate, modify_noisegate);
+        register_accessor!($asyncness $await "PLL N", PllN, read_plln, write_plln, modify_plln);
+        register_accessor!($asyncness $await "PLL K 1", PllK1, read_pllk1, write_pllk1, modify_pllk1);
+        register_accessor!($asyncness $await "PLL K 2", PllK2, read_pllk2, write_pllk2, modify_pllk2);
+        register_accessor!($asyncness $await "PLL K 3", PllK3, read_pllk3, write_pllk3, modify_pllk3);
+        register_accessor!($asyncness $await "3D control", ThreeDControl, read_threedcontrol, write_threedcontrol, modify_threedcontrol);
+        register_accessor!($asyncness $await "Right Speaker Submix", RightSpeakerSubmix, read_rightspeakersubmix, write_rightspeakersubmix, modify_rightspeakersubmix);
+        register_accessor!($asyncness $await "Input Control", InputControl, read_inputcontrol, write_inputcontrol, modify_inputcontrol);
+        register_accessor!($asyncness $await "Left Input PGA Gain", LeftInputPGAGain, read_leftinputpgagain, write_leftinputpgagain, modify_leftinputpgagain);
+        register_accessor!($asyncness $await "Right Input PGA Gain", RightInputPGAGain, read_rightinputpgagain, write_rightinputpgagain, modify_rightinputpgagain);
+        register_accessor!($asyncness $await "Left ADC Boost", LeftADCBoost, read_leftadcboost, write_leftadcboost, modify_leftadcboost);
+        register_accessor!($asyncness $await "Right ADC Boost", RightADCBoost, read_rightadcboost, write_rightadcboost, modify_rightadcboost);
+        register_accessor!($asyncness $await "Output Control", OutputControl, read_outputcontrol, write_outputcontrol, modify_outputcontrol);
+        register_accessor!($asyncness $await "Left Mixer", LeftMixer, read_leftmixer, write_leftmixer, modify_leftmixer);
+        register_accessor!($asyncness $await "Right Mixer", RightMixer, read_rightmixer, write_rightmixer, modify_rightmixer);
+        register_accessor!($asyncness $await "LHP Volume", LHPVolume, read_lhpvolume, write_lhpvolume, modify_lhpvolume);
+        register_accessor!($asyncness $await "RHP Volume", RHPVolume, read_rhpvolume, write_rhpvolume, modify_rhpvolume);
+        register_accessor!($asyncness $await "LSPKOUT Volume", LSPKOUTVolume, read_lspkoutvolume, write_lspkoutvolume, modify_lspkoutvolume);
+        register_accessor!($asyncness $await "RSPKOUT Volume", RSPKOUTVolume, read_rspkoutvolume, write_rspkoutvolume, modify_rspkoutvolume);
+        register_accessor!($asyncness $await "AUX2 Mixer", AUX2Mixer, read_aux2mixer, write_aux2mixer, modify_aux2mixer);
+        register_accessor!($asyncness $await "AUX1 Mixer", AUX1Mixer, read_aux1mixer, write_aux1mixer, modify_aux1mixer);
+        register_accessor!($asyncness $await "Power Management", PowerManagement, read_powermanagement, write_powermanagement, modify_powermanagement);
+        register_accessor!($asyncness $await "Left Time Slot", LeftTimeSlot, read_lefttimeslot, write_lefttimeslot, modify_lefttimeslot);
+        register_accessor!($asyncness $await "Misc", Misc, read_misc, write_misc, modify_misc);
+        register_accessor!($asyncness $await "Right Time Slot", RightTimeSlot, read_righttimeslot, write_righttimeslot, modify_righttimeslot);
+        register_accessor!($asyncness $await "Device Revision #", DeviceRevisionNo, read_devicerevisionno, write_devicerevisionno, modify_devicerevisionno);
+        register_accessor!($asyncness $await "Device ID", DeviceId, read_deviceid, write_deviceid, modify_deviceid);
+        register_accessor!($asyncness $await "DAC Dither", DacDither, read_dacdither, write_dacdither, modify_dacdither);
+        register_accessor!($asyncness $await "ALC Enhancements 1", AlcEnhancements1, read_alcenhancements1, write_alcenhancements1, modify_alcenhancements1);
+        register_accessor!($asyncness $await "ALC Enhancements 2", AlcEnhancements2, read_alcenhancements2, write_alcenhancements2, modify_alcenhancements2);
+        register_accessor!($asyncness $await "Misc Controls", MiscControls, read_misccontrols, write_misccontrols, modify_misccontrols);
+        register_accessor!($asyncness $await "Tie-Off Overrides", TieOffOverrides, read_tieoffoverrides, write_tieoffoverrides, modify_tieoffoverrides);
+        register_accessor!($asyncness $await "Power/Tie-off Ctrl", PowerTieOffCtrl, read_powertieoffctrl, write_powertieoffctrl, modify_powertieoffctrl);
+        register_accessor!($asyncness $await "P2P Detector Read", P2PDetectorRead, read_p2pdetectorread, write_p2pdetectorread, modify_p2pdetectorread);
+        register_accessor!($asyncness $await "Peak Detector Read", PeakDetectorRead, read_peakdetectorread, write_peakdetectorread, modify_peakdetectorread);
+        register_accessor!($asyncness $await "Control and Status", ControlAndStatus, read_controlandstatus, write_controlandstatus, modify_controlandstatus);
+        register_accessor!($asyncness $await "Output tie-off control", OutputTieOffControl, read_outputtieoffcontrol, write_outputtieoffcontrol, modify_outputtieoffcontrol);
+    };
+}
+
+/// Implement [`Default`] for each of the given register types, giving the
+/// register's power-on value (see `Register::default_value`).
+macro_rules! register_defaults {
+    ($($reg:ident),* $(,)?) => {
+        $(
+            impl Default for $reg {
+                fn default() -> Self {
+                    $reg(Register::$reg.default_value())
+                }
+            }
+        )*
+    };
+}
+
+// End of file
@@ -0,0 +1,140 @@
+//! Microphone bias configuration for the NAU88C22
+//!
+//! The MICBIAS pin supplies a fixed fraction of VDDA. The fractions on
+//! offer depend on whether the low-noise mode (with a 200 Ω output
+//! impedance) is selected - see [`InputControl::micbiasv`].
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::micbias::MicBias;
+//! // An electret mic wanting about 2.5V, on a 3.3V supply
+//! let settings = codec.set_mic_bias(&MicBias::new(3.3, 2.5))?;
+//! let volts = settings.bias_volts;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`InputControl::micbiasv`]: crate::registers::InputControl::micbiasv
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{Codec, Error, Interface};
+
+/// Describes the microphone bias we want
+///
+/// By default the normal (low impedance) mode is used.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MicBias {
+    vdda_volts: f32,
+    bias_volts: f32,
+    low_noise: bool,
+}
+
+impl MicBias {
+    /// The fraction of VDDA given by each `micbiasv` code, in normal mode
+    pub const NORMAL_RATIOS: [f32; 4] = [0.90, 0.65, 0.75, 0.50];
+    /// The fraction of VDDA given by each `micbiasv` code, in low-noise mode
+    pub const LOW_NOISE_RATIOS: [f32; 4] = [0.85, 0.60, 0.70, 0.50];
+
+    /// Describe a bias as close as possible to `bias_volts`, with the given
+    /// VDDA supply voltage.
+    pub const fn new(vdda_volts: f32, bias_volts: f32) -> MicBias {
+        MicBias {
+            vdda_volts,
+            bias_volts,
+            low_noise: false,
+        }
+    }
+
+    /// Use the low-noise mode, which has a 200 Ω output impedance
+    pub const fn low_noise(self, low_noise: bool) -> MicBias {
+        MicBias { low_noise, ..self }
+    }
+
+    /// Work out the register settings which get closest to the bias asked
+    /// for.
+    ///
+    /// Returns `None` if either voltage is not a positive number.
+    ///
+    /// ```
+    /// use nau88c22::micbias::MicBias;
+    /// let settings = MicBias::new(3.3, 2.5).compute().unwrap();
+    /// // 0.75 x VDDA
+    /// assert_eq!(settings.micbiasv, 2);
+    /// assert!((settings.bias_volts - 2.475).abs() < 0.001);
+    /// let settings = MicBias::new(3.3, 2.0).low_noise(true).compute().unwrap();
+    /// // 0.60 x VDDA
+    /// assert_eq!(settings.micbiasv, 1);
+    /// assert!(MicBias::new(0.0, 2.0).compute().is_none());
+    /// ```
+    pub fn compute(&self) -> Option<MicBiasSettings> {
+        if !(self.vdda_volts > 0.0 && self.bias_volts > 0.0) {
+            return None;
+        }
+        let ratios = if self.low_noise {
+            &Self::LOW_NOISE_RATIOS
+        } else {
+            &Self::NORMAL_RATIOS
+        };
+        let mut best = 0;
+        for (idx, ratio) in ratios.iter().enumerate() {
+            let error = libm::fabsf(ratio * self.vdda_volts - self.bias_volts);
+            if error < libm::fabsf(ratios[best] * self.vdda_volts - self.bias_volts) {
+                best = idx;
+            }
+        }
+        Some(MicBiasSettings {
+            micbiasv: best as u8,
+            low_noise: self.low_noise,
+            bias_volts: ratios[best] * self.vdda_volts,
+        })
+    }
+}
+
+/// The register settings for a [`MicBias`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MicBiasSettings {
+    /// The bias voltage code ([`registers::InputControl::micbiasv`])
+    ///
+    /// [`registers::InputControl::micbiasv`]: crate::registers::InputControl::micbiasv
+    pub micbiasv: u8,
+    /// Whether low-noise mode is on ([`registers::PowerManagement::micbiasm`])
+    ///
+    /// [`registers::PowerManagement::micbiasm`]: crate::registers::PowerManagement::micbiasm
+    pub low_noise: bool,
+    /// The open-circuit bias voltage these settings give
+    pub bias_volts: f32,
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up and turn on the microphone bias.
+    ///
+    /// Picks the closest voltage on offer in the selected mode, and returns
+    /// the settings used - which include the resulting bias voltage. Returns
+    /// [`Error::OutOfRange`] if either voltage is not a positive number.
+    pub fn set_mic_bias(&mut self, bias: &MicBias) -> Result<MicBiasSettings, Error<I::Error>> {
+        let settings = bias.compute().ok_or(Error::OutOfRange)?;
+        self.modify_powermanagement(|mut w| {
+            w.micbiasm_set(settings.low_noise);
+            w
+        })?;
+        self.modify_inputcontrol(|mut w| {
+            w.micbiasv_set(settings.micbiasv);
+            w
+        })?;
+        self.modify_powermanagement1(|mut w| {
+            w.micbiasen_set(true);
+            w
+        })?;
+        Ok(settings)
+    }
+}
+
+// End of file
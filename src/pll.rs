@@ -0,0 +1,219 @@
+//! PLL configuration for the NAU88C22
+//!
+//! The PLL multiplies the MCLK input (optionally divided by 2 first) by `N.K`,
+//! where `N` is a 4-bit integer and `K` is a 24-bit fraction. The PLL output
+//! is divided by 4, and then by the [`ClockControl1::mclksel`] divider, to
+//! make the internal 256 × fs master clock.
+//!
+//! The PLL output must be between 90 MHz and 100 MHz, and `N` must be between
+//! 6 and 12.
+//!
+//! [`ClockControl1::mclksel`]: crate::registers::ClockControl1::mclksel
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{registers, Codec, Error, Interface};
+
+/// The ways that PLL settings can be impossible
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PllError {
+    /// The MCLK input must be between 8 MHz and 33 MHz
+    MclkOutOfRange,
+    /// No [`ClockControl1::mclksel`](crate::registers::ClockControl1::mclksel)
+    /// divider puts the PLL output between 90 MHz and 100 MHz for this sample
+    /// rate
+    SampleRateOutOfRange,
+    /// The integer part of the ratio, `N`, would be outside 6 to 12
+    RatioOutOfRange,
+}
+
+/// A complete set of PLL settings
+///
+/// ```
+/// use nau88c22::pll::PllSettings;
+/// let settings = PllSettings::compute(12_000_000, 48_000).unwrap();
+/// assert_eq!(settings.prescale, false);
+/// assert_eq!(settings.n, 8);
+/// assert_eq!(settings.k, 0x3126E9);
+/// // divide by 2
+/// assert_eq!(settings.mclksel, 2);
+/// assert!(settings.error_ppm().abs() < 0.1);
+/// // K is rounded to the nearest, not truncated
+/// let settings = PllSettings::compute(12_288_000, 44_100).unwrap();
+/// assert_eq!((settings.n, settings.k), (7, 0x59999A));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PllSettings {
+    /// The MCLK input frequency these settings are for
+    pub mclk_hz: u32,
+    /// The sample rate these settings were asked for
+    pub sample_rate_hz: u32,
+    /// Divide MCLK by 2 before it reaches the PLL ([`registers::PllN::pllmclk`])
+    pub prescale: bool,
+    /// The integer part of the PLL ratio ([`registers::PllN::plln`])
+    pub n: u8,
+    /// The 24-bit fractional part of the PLL ratio
+    pub k: u32,
+    /// The PLL output divider ([`registers::ClockControl1::mclksel`])
+    pub mclksel: u8,
+}
+
+impl PllSettings {
+    /// Lowest MCLK frequency the PLL accepts
+    pub const MCLK_MIN_HZ: u32 = 8_000_000;
+    /// Highest MCLK frequency the PLL accepts
+    pub const MCLK_MAX_HZ: u32 = 33_000_000;
+    /// Lowest PLL output frequency
+    pub const PLL_MIN_HZ: u32 = 90_000_000;
+    /// Highest PLL output frequency
+    pub const PLL_MAX_HZ: u32 = 100_000_000;
+
+    /// The `mclksel` dividers, in half-units, indexed by register value
+    pub(crate) const MCLKSEL_HALVES: [u32; 8] = [2, 3, 4, 6, 8, 12, 16, 24];
+
+    /// Work out the PLL settings for a given MCLK and sample rate.
+    ///
+    /// Picks the `mclksel` divider which gives the highest PLL output frequency
+    /// in range, then works out the ratio to get there from MCLK.
+    pub fn compute(mclk_hz: u32, sample_rate_hz: u32) -> Result<PllSettings, PllError> {
+        if !(Self::MCLK_MIN_HZ..=Self::MCLK_MAX_HZ).contains(&mclk_hz) {
+            return Err(PllError::MclkOutOfRange);
+        }
+        // PLL output = 256 * fs * 4 * divider
+        let (mclksel, pll_hz) = Self::MCLKSEL_HALVES
+            .iter()
+            .enumerate()
+            .map(|(idx, halves)| {
+                (
+                    idx as u8,
+                    512 * u64::from(sample_rate_hz) * u64::from(*halves),
+                )
+            })
+            .rfind(|(_idx, pll_hz)| {
+                (u64::from(Self::PLL_MIN_HZ)..=u64::from(Self::PLL_MAX_HZ)).contains(pll_hz)
+            })
+            .ok_or(PllError::SampleRateOutOfRange)?;
+        // Ratio as 4.24 fixed point, rounded to the nearest
+        let fixed_ratio = |pll_hz: u64| (pll_hz + u64::from(mclk_hz) / 2) / u64::from(mclk_hz);
+        let mut ratio = fixed_ratio(pll_hz << 24);
+        let mut prescale = false;
+        if (ratio >> 24) < 6 {
+            ratio = fixed_ratio(pll_hz << 25);
+            prescale = true;
+        }
+        let n = (ratio >> 24) as u8;
+        if !(6..=12).contains(&n) {
+            return Err(PllError::RatioOutOfRange);
+        }
+        Ok(PllSettings {
+            mclk_hz,
+            sample_rate_hz,
+            prescale,
+            n,
+            k: (ratio & 0xFF_FFFF) as u32,
+            mclksel,
+        })
+    }
+
+    /// The frequency the PLL will actually run at
+    pub fn pll_hz(&self) -> f64 {
+        let input = if self.prescale {
+            f64::from(self.mclk_hz) / 2.0
+        } else {
+            f64::from(self.mclk_hz)
+        };
+        let ratio = f64::from(self.n) + (f64::from(self.k) / f64::from(1u32 << 24));
+        input * ratio
+    }
+
+    /// The sample rate these settings will actually produce
+    pub fn achieved_sample_rate_hz(&self) -> f64 {
+        let halves = Self::MCLKSEL_HALVES[usize::from(self.mclksel & 7)];
+        self.pll_hz() / (512.0 * f64::from(halves))
+    }
+
+    /// How far the achieved sample rate is from the one asked for, in parts
+    /// per million
+    pub fn error_ppm(&self) -> f64 {
+        let target = f64::from(self.sample_rate_hz);
+        (self.achieved_sample_rate_hz() - target) * 1_000_000.0 / target
+    }
+
+    /// The *PLL N register* contents for these settings
+    pub fn plln(&self) -> registers::PllN {
+        let mut value = registers::PllN(0);
+        value.pllmclk_set(self.prescale);
+        value.plln_set(self.n);
+        value
+    }
+
+    /// The *PLL K 1 register* contents for these settings
+    pub fn pllk1(&self) -> registers::PllK1 {
+        registers::PllK1(((self.k >> 18) & 0x3F) as u16)
+    }
+
+    /// The *PLL K 2 register* contents for these settings
+    pub fn pllk2(&self) -> registers::PllK2 {
+        registers::PllK2(((self.k >> 9) & 0x1FF) as u16)
+    }
+
+    /// The *PLL K 3 register* contents for these settings
+    pub fn pllk3(&self) -> registers::PllK3 {
+        registers::PllK3((self.k & 0x1FF) as u16)
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up the PLL to make the given sample rate from the given MCLK.
+    ///
+    /// Writes all four PLL registers, powers up the PLL, and then selects it
+    /// as the master clock source with the right divider, so the CODEC is
+    /// never clocked from a PLL which isn't running. Returns the settings
+    /// used, which can tell you the achieved sample rate.
+    ///
+    /// ```rust,no_run
+    /// # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+    /// let settings = codec.configure_pll(12_288_000, 44_100)?;
+    /// let error_ppm = settings.error_ppm();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn configure_pll(
+        &mut self,
+        mclk_hz: u32,
+        sample_rate_hz: u32,
+    ) -> Result<PllSettings, Error<I::Error>> {
+        let settings = PllSettings::compute(mclk_hz, sample_rate_hz).map_err(Error::Pll)?;
+        self.write_pll(&settings)?;
+        self.modify_clockcontrol1(|mut w| {
+            w.clkm_set(true);
+            w.mclksel_set(settings.mclksel);
+            w
+        })?;
+        Ok(settings)
+    }
+
+    /// Write all four PLL registers, and then power up the PLL.
+    ///
+    /// Selecting the PLL as the master clock source is left to the caller,
+    /// and must only happen after this.
+    pub(crate) fn write_pll(&mut self, settings: &PllSettings) -> Result<(), Error<I::Error>> {
+        self.write_plln(settings.plln())?;
+        self.write_pllk1(settings.pllk1())?;
+        self.write_pllk2(settings.pllk2())?;
+        self.write_pllk3(settings.pllk3())?;
+        self.modify_powermanagement1(|mut w| {
+            w.pllen_set(true);
+            w
+        })
+    }
+}
+
+// End of file
@@ -0,0 +1,544 @@
+//! Power-up and power-down sequencing for the NAU88C22
+//!
+//! The analog outputs will pop loudly if the CODEC is powered up in the
+//! wrong order. The datasheet recommends:
+//!
+//! 1. Set up the output tie-offs and boost settings for the supply voltage
+//! 2. Charge the VREF capacitor through the 80 kΩ reference string, with the
+//!    analog bias and tie-off buffers on
+//! 3. Switch to the 300 kΩ reference string once VREF has settled
+//! 4. Mute the outputs
+//! 5. Power the converters and mixers
+//! 6. Power the output drivers
+//! 7. Unmute the outputs
+//!
+//! Powering down runs roughly the other way around. Each step is available
+//! on its own (see [`Codec::power_up_step`]), so you can check what each one
+//! writes, or run them from your own scheduler.
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{Codec, Error, Interface};
+
+/// Describes which parts of the CODEC to power up
+///
+/// By default nothing but the references is powered, the outputs are set up
+/// for a supply of 3.6V or less, and VREF is given 250 ms to charge.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerUpConfig {
+    dac: bool,
+    adc: bool,
+    headphones: bool,
+    speakers: bool,
+    aux_outputs: bool,
+    high_voltage: bool,
+    vref_charge_ms: u32,
+}
+
+impl PowerUpConfig {
+    /// Describe a CODEC with only the references powered
+    pub const fn new() -> PowerUpConfig {
+        PowerUpConfig {
+            dac: false,
+            adc: false,
+            headphones: false,
+            speakers: false,
+            aux_outputs: false,
+            high_voltage: false,
+            vref_charge_ms: 250,
+        }
+    }
+
+    /// Power both DACs
+    pub const fn dac(self, dac: bool) -> PowerUpConfig {
+        PowerUpConfig { dac, ..self }
+    }
+
+    /// Power both ADCs
+    pub const fn adc(self, adc: bool) -> PowerUpConfig {
+        PowerUpConfig { adc, ..self }
+    }
+
+    /// Power both headphone drivers, and the main mixers which feed them
+    pub const fn headphones(self, headphones: bool) -> PowerUpConfig {
+        PowerUpConfig { headphones, ..self }
+    }
+
+    /// Power both loudspeaker drivers, and the main mixers which feed them
+    pub const fn speakers(self, speakers: bool) -> PowerUpConfig {
+        PowerUpConfig { speakers, ..self }
+    }
+
+    /// Power both AUX outputs, and the AUX mixers which feed them
+    pub const fn aux_outputs(self, aux_outputs: bool) -> PowerUpConfig {
+        PowerUpConfig {
+            aux_outputs,
+            ..self
+        }
+    }
+
+    /// Set the loudspeaker and AUX outputs up for a supply of more than 3.6V.
+    ///
+    /// This selects the 1.5x output gain boost, and powers the tie-off buffer
+    /// which goes with it.
+    pub const fn high_voltage(self, high_voltage: bool) -> PowerUpConfig {
+        PowerUpConfig {
+            high_voltage,
+            ..self
+        }
+    }
+
+    /// How long to let the VREF capacitor charge for.
+    ///
+    /// This depends on the size of the capacitor on the VREF pin.
+    pub const fn vref_charge_ms(self, vref_charge_ms: u32) -> PowerUpConfig {
+        PowerUpConfig {
+            vref_charge_ms,
+            ..self
+        }
+    }
+
+    /// Every output powered, and nothing else
+    const EVERY_OUTPUT: PowerUpConfig = PowerUpConfig::new()
+        .headphones(true)
+        .speakers(true)
+        .aux_outputs(true);
+
+    /// Are the main mixers needed?
+    const fn mixers(&self) -> bool {
+        self.headphones || self.speakers
+    }
+}
+
+impl Default for PowerUpConfig {
+    fn default() -> Self {
+        PowerUpConfig::new()
+    }
+}
+
+/// The steps taken by [`Codec::power_up`], in order
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerUpStep {
+    /// Set the output boost and thermal shutdown in *Output Control*
+    TieOff,
+    /// Turn on the bias and tie-off buffers, and charge VREF through 80 kΩ
+    ChargeReference,
+    /// Switch VREF to the 300 kΩ reference string
+    HoldReference,
+    /// Mute the headphone, loudspeaker and AUX outputs
+    MuteOutputs,
+    /// Power the DACs, ADCs and main mixers
+    EnableConverters,
+    /// Power the output drivers, and the AUX mixers
+    EnableOutputs,
+    /// Unmute the outputs which are powered, leaving the rest muted
+    UnmuteOutputs,
+}
+
+impl PowerUpStep {
+    /// Every step, in the order they should be run
+    pub const ALL: [PowerUpStep; 7] = [
+        PowerUpStep::TieOff,
+        PowerUpStep::ChargeReference,
+        PowerUpStep::HoldReference,
+        PowerUpStep::MuteOutputs,
+        PowerUpStep::EnableConverters,
+        PowerUpStep::EnableOutputs,
+        PowerUpStep::UnmuteOutputs,
+    ];
+}
+
+/// The steps taken by [`Codec::power_down`], in order
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerDownStep {
+    /// Mute the headphone, loudspeaker and AUX outputs
+    MuteOutputs,
+    /// Turn off the output drivers, and the AUX mixers
+    DisableOutputs,
+    /// Turn off the DACs, ADCs and main mixers
+    DisableConverters,
+    /// Turn off VREF, and the bias and tie-off buffers
+    ReleaseReference,
+}
+
+impl PowerDownStep {
+    /// Every step, in the order they should be run
+    pub const ALL: [PowerDownStep; 4] = [
+        PowerDownStep::MuteOutputs,
+        PowerDownStep::DisableOutputs,
+        PowerDownStep::DisableConverters,
+        PowerDownStep::ReleaseReference,
+    ];
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Power up the CODEC without pops or clicks.
+    ///
+    /// Runs every [`PowerUpStep`] in order, waiting for VREF to charge where
+    /// needed. The input PGAs and boost stages are left alone, as they
+    /// belong to the input configuration.
+    ///
+    /// ```rust,no_run
+    /// # fn example<I, D>(codec: &mut nau88c22::Codec<I>, delay: &mut D) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface, D: embedded_hal::delay::DelayNs {
+    /// use nau88c22::power::PowerUpConfig;
+    /// let config = PowerUpConfig::new().dac(true).headphones(true);
+    /// codec.power_up(&config, delay)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn power_up<D>(
+        &mut self,
+        config: &PowerUpConfig,
+        delay: &mut D,
+    ) -> Result<(), Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        for step in PowerUpStep::ALL {
+            let delay_ms = self.power_up_step(config, step)?;
+            if delay_ms != 0 {
+                delay.delay_ms(delay_ms);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a single step of the power-up sequence.
+    ///
+    /// Returns how many milliseconds to wait before the next step.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    /// impl MockI2c {
+    ///     /// A chip which has just been reset
+    ///     fn new() -> MockI2c {
+    ///         let mut registers = [0; 0x50];
+    ///         for register in nau88c22::Register::readable() {
+    ///             registers[*register as usize] = register.default_value();
+    ///         }
+    ///         MockI2c { registers, writes: Vec::new() }
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::power::{PowerUpConfig, PowerUpStep};
+    /// let config = PowerUpConfig::new().dac(true).headphones(true);
+    /// let mut codec = nau88c22::Codec::new(MockI2c::new());
+    /// let delays: Vec<u32> = PowerUpStep::ALL
+    ///     .iter()
+    ///     .map(|step| codec.power_up_step(&config, *step).unwrap())
+    ///     .collect();
+    /// // Wait for VREF to charge after ChargeReference
+    /// assert_eq!(delays, [0, 250, 0, 0, 0, 0, 0]);
+    /// let i2c = codec.release();
+    /// assert_eq!(
+    ///     i2c.writes,
+    ///     [
+    ///         // TieOff: no boost, thermal shutdown on
+    ///         (0x31, 0x002),
+    ///         // ChargeReference: abiasen, iobufen and 80 kΩ reference
+    ///         (0x01, 0x00D),
+    ///         // HoldReference: 300 kΩ reference
+    ///         (0x01, 0x00E),
+    ///         // MuteOutputs: every output, latching each pair on the right
+    ///         (0x34, 0x079), (0x35, 0x179), (0x36, 0x079), (0x37, 0x179), (0x39, 0x041), (0x38, 0x041),
+    ///         // EnableConverters: ldacen, rdacen, lmixen and rmixen
+    ///         (0x02, 0x000), (0x03, 0x00F),
+    ///         // EnableOutputs: lhpen and rhpen, leaving the reference alone
+    ///         (0x02, 0x180), (0x03, 0x00F), (0x01, 0x00E),
+    ///         // UnmuteOutputs: only the headphones, which are powered
+    ///         (0x34, 0x039), (0x35, 0x139),
+    ///     ]
+    /// );
+    /// ```
+    pub fn power_up_step(
+        &mut self,
+        config: &PowerUpConfig,
+        step: PowerUpStep,
+    ) -> Result<u32, Error<I::Error>> {
+        match step {
+            PowerUpStep::TieOff => {
+                self.modify_outputcontrol(|mut w| {
+                    w.aux1bst_set(config.high_voltage);
+                    w.aux2bst_set(config.high_voltage);
+                    w.spkbst_set(config.high_voltage);
+                    w.tsen_set(true);
+                    w
+                })?;
+            }
+            PowerUpStep::ChargeReference => {
+                self.modify_powermanagement1(|mut w| {
+                    w.dcbufen_set(config.high_voltage);
+                    w.abiasen_set(true);
+                    w.iobufen_set(true);
+                    w.refimp_set(1);
+                    w
+                })?;
+                return Ok(config.vref_charge_ms);
+            }
+            PowerUpStep::HoldReference => {
+                self.modify_powermanagement1(|mut w| {
+                    w.refimp_set(2);
+                    w
+                })?;
+            }
+            PowerUpStep::MuteOutputs => {
+                self.set_output_mutes(&PowerUpConfig::EVERY_OUTPUT, true)?;
+            }
+            PowerUpStep::EnableConverters => {
+                self.modify_powermanagement2(|mut w| {
+                    w.ladcen_set(config.adc);
+                    w.radcen_set(config.adc);
+                    w
+                })?;
+                self.modify_powermanagement3(|mut w| {
+                    w.ldacen_set(config.dac);
+                    w.rdacen_set(config.dac);
+                    w.lmixen_set(config.mixers());
+                    w.rmixen_set(config.mixers());
+                    w
+                })?;
+            }
+            PowerUpStep::EnableOutputs => {
+                self.modify_powermanagement2(|mut w| {
+                    w.lhpen_set(config.headphones);
+                    w.rhpen_set(config.headphones);
+                    w
+                })?;
+                self.modify_powermanagement3(|mut w| {
+                    w.lspken_set(config.speakers);
+                    w.rspken_set(config.speakers);
+                    w.auxout1en_set(config.aux_outputs);
+                    w.auxout2en_set(config.aux_outputs);
+                    w
+                })?;
+                self.modify_powermanagement1(|mut w| {
+                    w.aux1mxen_set(config.aux_outputs);
+                    w.aux2mxen_set(config.aux_outputs);
+                    w
+                })?;
+            }
+            PowerUpStep::UnmuteOutputs => self.set_output_mutes(config, false)?,
+        }
+        Ok(0)
+    }
+
+    /// Power down the CODEC without pops or clicks.
+    ///
+    /// Runs every [`PowerDownStep`] in order. The PLL and microphone bias are
+    /// left alone.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    /// impl MockI2c {
+    ///     /// A chip which has just been reset
+    ///     fn new() -> MockI2c {
+    ///         let mut registers = [0; 0x50];
+    ///         for register in nau88c22::Register::readable() {
+    ///             registers[*register as usize] = register.default_value();
+    ///         }
+    ///         MockI2c { registers, writes: Vec::new() }
+    ///     }
+    /// }
+    /// # struct NoDelay;
+    /// # impl embedded_hal::delay::DelayNs for NoDelay {
+    /// #     fn delay_ns(&mut self, _ns: u32) {}
+    /// # }
+    ///
+    /// use nau88c22::power::PowerUpConfig;
+    /// let mut codec = nau88c22::Codec::new(MockI2c::new());
+    /// let config = PowerUpConfig::new().dac(true).speakers(true);
+    /// codec.power_up(&config, &mut NoDelay).unwrap();
+    /// let mut i2c = codec.release();
+    /// i2c.writes.clear();
+    /// let mut codec = nau88c22::Codec::new(i2c);
+    /// codec.power_down().unwrap();
+    /// let i2c = codec.release();
+    /// assert_eq!(
+    ///     i2c.writes,
+    ///     [
+    ///         // MuteOutputs
+    ///         (0x34, 0x079), (0x35, 0x179), (0x36, 0x079), (0x37, 0x179), (0x39, 0x041), (0x38, 0x041),
+    ///         // DisableOutputs: lspken and rspken off
+    ///         (0x02, 0x000), (0x03, 0x00F), (0x01, 0x00E),
+    ///         // DisableConverters
+    ///         (0x02, 0x000), (0x03, 0x000),
+    ///         // ReleaseReference
+    ///         (0x01, 0x000),
+    ///     ]
+    /// );
+    /// ```
+    pub fn power_down(&mut self) -> Result<(), Error<I::Error>> {
+        for step in PowerDownStep::ALL {
+            self.power_down_step(step)?;
+        }
+        Ok(())
+    }
+
+    /// Run a single step of the power-down sequence.
+    pub fn power_down_step(&mut self, step: PowerDownStep) -> Result<(), Error<I::Error>> {
+        match step {
+            PowerDownStep::MuteOutputs => self.set_output_mutes(&PowerUpConfig::EVERY_OUTPUT, true),
+            PowerDownStep::DisableOutputs => {
+                self.modify_powermanagement2(|mut w| {
+                    w.lhpen_set(false);
+                    w.rhpen_set(false);
+                    w
+                })?;
+                self.modify_powermanagement3(|mut w| {
+                    w.lspken_set(false);
+                    w.rspken_set(false);
+                    w.auxout1en_set(false);
+                    w.auxout2en_set(false);
+                    w
+                })?;
+                self.modify_powermanagement1(|mut w| {
+                    w.aux1mxen_set(false);
+                    w.aux2mxen_set(false);
+                    w
+                })
+            }
+            PowerDownStep::DisableConverters => {
+                self.modify_powermanagement2(|mut w| {
+                    w.ladcen_set(false);
+                    w.radcen_set(false);
+                    w
+                })?;
+                self.modify_powermanagement3(|mut w| {
+                    w.ldacen_set(false);
+                    w.rdacen_set(false);
+                    w.lmixen_set(false);
+                    w.rmixen_set(false);
+                    w
+                })
+            }
+            PowerDownStep::ReleaseReference => self.modify_powermanagement1(|mut w| {
+                w.dcbufen_set(false);
+                w.abiasen_set(false);
+                w.iobufen_set(false);
+                w.refimp_set(0);
+                w
+            }),
+        }
+    }
+
+    /// Mute or unmute the headphone, loudspeaker and AUX outputs which
+    /// `outputs` powers. The other outputs are left alone.
+    ///
+    /// The right-hand registers are written with their update bits set, so
+    /// both channels change together.
+    fn set_output_mutes(
+        &mut self,
+        outputs: &PowerUpConfig,
+        mute: bool,
+    ) -> Result<(), Error<I::Error>> {
+        if outputs.headphones {
+            self.modify_lhpvolume(|mut w| {
+                w.lhpmute_set(mute);
+                w
+            })?;
+            self.modify_rhpvolume(|mut w| {
+                w.rhpmute_set(mute);
+                w.rhpvu_set(true);
+                w
+            })?;
+        }
+        if outputs.speakers {
+            self.modify_lspkoutvolume(|mut w| {
+                w.lspkmute_set(mute);
+                w
+            })?;
+            self.modify_rspkoutvolume(|mut w| {
+                w.rspkmute_set(mute);
+                w.rspkvu_set(true);
+                w
+            })?;
+        }
+        if outputs.aux_outputs {
+            self.modify_aux1mixer(|mut w| {
+                w.auxout1mt_set(mute);
+                w
+            })?;
+            self.modify_aux2mixer(|mut w| {
+                w.auxout2mt_set(mute);
+                w
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// End of file
@@ -227,6 +227,153 @@ impl Register {
 
         &REGISTERS
     }
+
+    /// The value this register holds after power-on or a software reset.
+    ///
+    /// Taken from the register map in the datasheet. Every register type also
+    /// implements [`Default`] using this value:
+    ///
+    /// ```
+    /// use nau88c22::registers::{AudioInterface, LHPVolume, Register};
+    /// assert_eq!(Register::LHPVolume.default_value(), 0x039);
+    /// use nau88c22::registers::{AudioFormat, WordLength};
+    /// let aif = AudioInterface::default();
+    /// assert_eq!(aif.aifmt(), AudioFormat::I2s);
+    /// assert_eq!(aif.wlen(), WordLength::Bits24);
+    /// // 0 dB
+    /// assert_eq!(LHPVolume::default().lhpgain(), 57);
+    /// ```
+    pub const fn default_value(self) -> u16 {
+        match self {
+            Register::SoftwareReset => 0x000,
+            Register::PowerManagement1 => 0x000,
+            Register::PowerManagement2 => 0x000,
+            Register::PowerManagement3 => 0x000,
+            Register::AudioInterface => 0x050,
+            Register::Companding => 0x000,
+            Register::ClockControl1 => 0x140,
+            Register::ClockControl2 => 0x000,
+            Register::GPIO => 0x000,
+            Register::JackDetect1 => 0x000,
+            Register::DACControl => 0x000,
+            Register::LeftDACVolume => 0x0FF,
+            Register::RightDACVolume => 0x0FF,
+            Register::JackDetect2 => 0x000,
+            Register::ADCControl => 0x100,
+            Register::LeftADCVolume => 0x0FF,
+            Register::RightADCVolume => 0x0FF,
+            Register::EQ1HighCutoff => 0x12C,
+            Register::EQ2Peak1 => 0x02C,
+            Register::EQ3Peak2 => 0x02C,
+            Register::EQ4Peak3 => 0x02C,
+            Register::EQ5LowCutoff => 0x02C,
+            Register::DACLimiter1 => 0x032,
+            Register::DACLimiter2 => 0x000,
+            Register::NotchFilter1 => 0x000,
+            Register::NotchFilter2 => 0x000,
+            Register::NotchFilter3 => 0x000,
+            Register::NotchFilter4 => 0x000,
+            Register::ALCControl1 => 0x038,
+            Register::ALCControl2 => 0x00B,
+            Register::ALCControl3 => 0x032,
+            Register::NoiseGate => 0x010,
+            Register::PllN => 0x008,
+            Register::PllK1 => 0x00C,
+            Register::PllK2 => 0x093,
+            Register::PllK3 => 0x0E9,
+            Register::ThreeDControl => 0x000,
+            Register::RightSpeakerSubmix => 0x000,
+            Register::InputControl => 0x033,
+            Register::LeftInputPGAGain => 0x010,
+            Register::RightInputPGAGain => 0x010,
+            Register::LeftADCBoost => 0x100,
+            Register::RightADCBoost => 0x100,
+            Register::OutputControl => 0x002,
+            Register::LeftMixer => 0x001,
+            Register::RightMixer => 0x001,
+            Register::LHPVolume => 0x039,
+            Register::RHPVolume => 0x039,
+            Register::LSPKOUTVolume => 0x039,
+            Register::RSPKOUTVolume => 0x039,
+            Register::AUX2Mixer => 0x001,
+            Register::AUX1Mixer => 0x001,
+            Register::PowerManagement => 0x000,
+            Register::LeftTimeSlot => 0x000,
+            Register::Misc => 0x020,
+            Register::RightTimeSlot => 0x000,
+            Register::DeviceRevisionNo => 0x07F,
+            Register::DeviceId => 0x01A,
+            Register::DacDither => 0x114,
+            Register::AlcEnhancements1 => 0x000,
+            Register::AlcEnhancements2 => 0x000,
+            Register::MiscControls => 0x000,
+            Register::TieOffOverrides => 0x000,
+            Register::PowerTieOffCtrl => 0x000,
+            Register::P2PDetectorRead => 0x000,
+            Register::PeakDetectorRead => 0x000,
+            Register::ControlAndStatus => 0x000,
+            Register::OutputTieOffControl => 0x000,
+        }
+    }
+
+    /// The bits in this register which can be written but not read back.
+    ///
+    /// These are the *update* bits which latch a pending left/right (or notch
+    /// filter) value pair. Whatever the chip returns for them on a read should
+    /// be ignored, and they should not be written back on a read-modify-write.
+    pub const fn write_only_mask(self) -> u16 {
+        match self {
+            Register::SoftwareReset => 0x1FF,
+            Register::LeftDACVolume
+            | Register::RightDACVolume
+            | Register::LeftADCVolume
+            | Register::RightADCVolume
+            | Register::NotchFilter1
+            | Register::NotchFilter2
+            | Register::NotchFilter3
+            | Register::NotchFilter4
+            | Register::LeftInputPGAGain
+            | Register::RightInputPGAGain
+            | Register::LHPVolume
+            | Register::RHPVolume
+            | Register::LSPKOUTVolume
+            | Register::RSPKOUTVolume => 0x100,
+            _ => 0x000,
+        }
+    }
+
+    /// The bits in this register which can be read but not written.
+    ///
+    /// Registers where this is `0x1FF` are entirely read-only.
+    pub const fn read_only_mask(self) -> u16 {
+        match self {
+            Register::DeviceRevisionNo
+            | Register::DeviceId
+            | Register::P2PDetectorRead
+            | Register::PeakDetectorRead => 0x1FF,
+            Register::AlcEnhancements1 | Register::AlcEnhancements2 => 0x03F,
+            Register::ControlAndStatus => 0x01F,
+            _ => 0x000,
+        }
+    }
+
+    /// Is this register left out of the register cache?
+    ///
+    /// These registers are either write-only, or contain nothing but
+    /// read-only status or identification values.
+    ///
+    /// Registers which mix settings with read-only status bits (see
+    /// [`Register::read_only_mask`]) are cached, less their read-only bits.
+    pub const fn is_volatile(self) -> bool {
+        matches!(
+            self,
+            Register::SoftwareReset
+                | Register::DeviceRevisionNo
+                | Register::DeviceId
+                | Register::P2PDetectorRead
+                | Register::PeakDetectorRead
+        )
+    }
 }
 
 bitfield! {
@@ -392,6 +539,108 @@ bitfield! {
     pub ldacen, ldacen_set: 0;
 }
 
+/// The audio data formats the digital audio interface supports
+///
+/// PCM A and PCM B both use an [`AudioInterface::aifmt`] value of `3`, and are
+/// told apart by [`AudioInterface::lrp`]. Converting from a `u8` therefore
+/// gives [`AudioFormat::PcmA`] for `3` - use
+/// [`AudioInterface::audio_format`] to look at both fields.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AudioFormat {
+    /// Right justified
+    RightJustified,
+    /// Left justified
+    LeftJustified,
+    /// Standard I²S
+    I2s,
+    /// PCM A (MSB is valid on 2nd rising edge of BCLK after rising edge of FS)
+    PcmA,
+    /// PCM B (MSB is valid on 1st rising edge of BCLK after rising edge of FS)
+    PcmB,
+}
+
+impl From<u8> for AudioFormat {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => AudioFormat::RightJustified,
+            1 => AudioFormat::LeftJustified,
+            2 => AudioFormat::I2s,
+            _ => AudioFormat::PcmA,
+        }
+    }
+}
+
+impl From<AudioFormat> for u8 {
+    fn from(value: AudioFormat) -> Self {
+        match value {
+            AudioFormat::RightJustified => 0,
+            AudioFormat::LeftJustified => 1,
+            AudioFormat::I2s => 2,
+            AudioFormat::PcmA | AudioFormat::PcmB => 3,
+        }
+    }
+}
+
+/// The word lengths the digital audio interface supports
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WordLength {
+    /// 16-bit words
+    Bits16 = 0,
+    /// 20-bit words
+    Bits20 = 1,
+    /// 24-bit words
+    Bits24 = 2,
+    /// 32-bit words
+    Bits32 = 3,
+}
+
+impl WordLength {
+    /// The number of bits in a word
+    pub const fn bits(self) -> u8 {
+        match self {
+            WordLength::Bits16 => 16,
+            WordLength::Bits20 => 20,
+            WordLength::Bits24 => 24,
+            WordLength::Bits32 => 32,
+        }
+    }
+}
+
+impl From<u8> for WordLength {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => WordLength::Bits16,
+            1 => WordLength::Bits20,
+            2 => WordLength::Bits24,
+            _ => WordLength::Bits32,
+        }
+    }
+}
+
+impl From<WordLength> for u8 {
+    fn from(value: WordLength) -> Self {
+        value as u8
+    }
+}
+
+/// The clock polarities of the digital audio interface
+///
+/// The default is normal polarity for both clocks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AudioPolarity {
+    /// Invert BCLK ([`AudioInterface::bclkp`])
+    pub bclk_inverted: bool,
+    /// Invert FS ([`AudioInterface::lrp`])
+    ///
+    /// Ignored for [`AudioFormat::PcmA`] and [`AudioFormat::PcmB`], where
+    /// `lrp` selects between the two PCM formats instead.
+    pub frame_inverted: bool,
+}
+
 bitfield! {
     /// Audio Interface register contents
     ///
@@ -420,14 +669,14 @@ bitfield! {
     /// * `1` = 20-bit word length
     /// * `2` = 24-bit word length
     /// * `3` = 32-bit word length
-    pub wlen, wlen_set: 6,5;
+    pub into WordLength, wlen, wlen_set: 6,5;
     /// Audio interface data format (default setting is I2S)
     ///
     /// * `0` = right justified
     /// * `1` = left justified
     /// * `2` = standard I2S format
     /// * `3` = PCMA or PCMB audio data format option
-    pub aifmt, aifmt_set: 4,3;
+    pub into AudioFormat, aifmt, aifmt_set: 4,3;
     /// DAC audio data left-right ordering
     ///
     /// * `false` = left DAC data in left phase of LRP
@@ -446,6 +695,26 @@ bitfield! {
     pub mono, mono_set: 0;
 }
 
+impl AudioInterface {
+    /// Get the audio data format, telling PCM A from PCM B using
+    /// [`AudioInterface::lrp`]
+    ///
+    /// ```
+    /// use nau88c22::registers::{AudioFormat, AudioInterface};
+    /// let mut aif = AudioInterface::default();
+    /// aif.aifmt_set(AudioFormat::PcmB.into());
+    /// assert_eq!(aif.audio_format(), AudioFormat::PcmA);
+    /// aif.lrp_set(true);
+    /// assert_eq!(aif.audio_format(), AudioFormat::PcmB);
+    /// ```
+    pub fn audio_format(&self) -> AudioFormat {
+        match self.aifmt() {
+            AudioFormat::PcmA | AudioFormat::PcmB if self.lrp() => AudioFormat::PcmB,
+            format => format,
+        }
+    }
+}
+
 /// Whether 8-bit companding is enabled
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(u8)]
@@ -1100,7 +1369,7 @@ bitfield! {
     /// * `9` = 34.8 ms
     /// * `10` = 69.6 ms
     /// * `11` through `15` = 139 ms
-    pub daclimatk, daclimatk_set: 4, 0;
+    pub daclimatk, daclimatk_set: 3, 0;
 }
 
 bitfield! {
@@ -1338,7 +1607,8 @@ bitfield! {
     /// | -------------------------- | -------------------------- |
     /// | `0` = 500 µs               | `0` = 125 µs               |
     /// | `1` = 1.0 ms               | `1` = 250 µs               |
-    /// | `2` = 2.0 ms (default)     | `2` = 500 µs (default)     |
+    /// | `2` = 2.0 ms               | `2` = 500 µs               |
+    /// | `3` = 4.0 ms (default)     | `3` = 1.0 ms (default)     |
     /// | ...                        | ...                        |
     /// | `8` = 128 ms               | `8` = 32 ms                |
     /// | `9` = 256 ms               | `9` = 64 ms                |
@@ -1551,13 +1821,13 @@ bitfield! {
     /// RMICN right microphone negative input to right PGA negative input path
     /// control
     ///
-    /// * `false` = RMICN not connected to PGA negative input (default)
-    /// * `true` = RMICN connected to PGA negative input
+    /// * `false` = RMICN not connected to PGA negative input
+    /// * `true` = RMICN connected to PGA negative input (default)
     pub rmicnrpga, rmicnrpga_set: 5;
     /// RMICP right microphone positive input to right PGA positive input enable
     ///
-    /// * `false` = RMICP not connected to PGA positive input (default)
-    /// * `true` = RMICP connected to PGA positive input
+    /// * `false` = RMICP not connected to PGA positive input
+    /// * `true` = RMICP connected to PGA positive input (default)
     pub rmicprpga, rmicprpga_set: 4;
     /// LLIN right line input path control to left PGA positive input
     ///
@@ -1567,13 +1837,13 @@ bitfield! {
     /// LMICN left microphone negative input to left PGA negative input path
     /// control
     ///
-    /// * `false` = LMICN not connected to PGA negative input (default)
-    /// * `true` = LMICN connected to PGA negative input
+    /// * `false` = LMICN not connected to PGA negative input
+    /// * `true` = LMICN connected to PGA negative input (default)
     pub lmicnlpga, lmicnlpga_set: 1;
     /// LMICP left microphone positive input to left PGA positive input enable
     ///
-    /// * `false` = LMICP not connected to PGA positive input (default)
-    /// * `true` = LMICP connected to PGA positive input
+    /// * `false` = LMICP not connected to PGA positive input
+    /// * `true` = LMICP connected to PGA positive input (default)
     pub lmicplpga, lmicplpga_set: 0;
 }
 
@@ -1829,8 +2099,8 @@ bitfield! {
     pub lbyplmx, lbyplmx_set: 1;
     /// Left DAC output to LMIX left output mixer path control
     ///
-    /// * `false` = path disconnected (default)
-    /// * `true` = path connected
+    /// * `false` = path disconnected
+    /// * `true` = path connected (default)
     pub ldaclmx, ldaclmx_set: 0;
 }
 
@@ -1880,8 +2150,8 @@ bitfield! {
     pub rbyprmx, rbyprmx_set: 1;
     /// Right DAC output to RMIX right output mixer path control
     ///
-    /// * `false` = path disconnected (default)
-    /// * `true` = path connected
+    /// * `false` = path disconnected
+    /// * `true` = path connected (default)
     pub rdacrmx, rdacrmx_set: 0;
 }
 
@@ -2368,7 +2638,7 @@ bitfield! {
     /// * `true` = disabled
     pub pklimena, pklimena_set: 8;
     /// Real time readout of instantaneous gain value used by right channel PGA
-    pub alcgainl, _: 5, 0;
+    pub alcgainr, _: 5, 0;
 }
 
 bitfield! {
@@ -2577,7 +2847,7 @@ bitfield! {
     /// [`modify_peakdetectorread`](crate::Codec::modify_peakdetectorread)
     pub struct PeakDetectorRead(u16);
     impl Debug;
-    u8;
+    u16;
     /// Read-only register which outputs the instantaneous value contained in
     /// the peak detector amplitude register used by the ALC for signal level
     /// dependent logic. Value is highest of left or right input when both
@@ -2708,4 +2978,74 @@ bitfield! {
     pub shrtrhp, shrtrhp_set: 0;
 }
 
+register_defaults!(
+    PowerManagement1,
+    PowerManagement2,
+    PowerManagement3,
+    AudioInterface,
+    Companding,
+    ClockControl1,
+    ClockControl2,
+    GPIO,
+    JackDetect1,
+    DACControl,
+    LeftDACVolume,
+    RightDACVolume,
+    JackDetect2,
+    ADCControl,
+    LeftADCVolume,
+    RightADCVolume,
+    EQ1HighCutoff,
+    EQ2Peak1,
+    EQ3Peak2,
+    EQ4Peak3,
+    EQ5LowCutoff,
+    DACLimiter1,
+    DACLimiter2,
+    NotchFilter1,
+    NotchFilter2,
+    NotchFilter3,
+    NotchFilter4,
+    ALCControl1,
+    ALCControl2,
+    ALCControl3,
+    NoiseGate,
+    PllN,
+    PllK1,
+    PllK2,
+    PllK3,
+    ThreeDControl,
+    RightSpeakerSubmix,
+    InputControl,
+    LeftInputPGAGain,
+    RightInputPGAGain,
+    LeftADCBoost,
+    RightADCBoost,
+    OutputControl,
+    LeftMixer,
+    RightMixer,
+    LHPVolume,
+    RHPVolume,
+    LSPKOUTVolume,
+    RSPKOUTVolume,
+    AUX2Mixer,
+    AUX1Mixer,
+    PowerManagement,
+    LeftTimeSlot,
+    Misc,
+    RightTimeSlot,
+    DeviceRevisionNo,
+    DeviceId,
+    DacDither,
+    AlcEnhancements1,
+    AlcEnhancements2,
+    MiscControls,
+    TieOffOverrides,
+    PowerTieOffCtrl,
+    P2PDetectorRead,
+    PeakDetectorRead,
+    ControlAndStatus,
+    OutputTieOffControl,
+);
+
 // End of file
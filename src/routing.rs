@@ -0,0 +1,554 @@
+//! Signal routing for the NAU88C22
+//!
+//! The analog outputs are fed by four mixers:
+//!
+//! * The left main mixer (LMIX) feeds both LHP and LSPKOUT
+//! * The right main mixer (RMIX) feeds both RHP and RSPKOUT
+//! * The AUX1 mixer feeds AUXOUT1
+//! * The AUX2 mixer feeds AUXOUT2
+//!
+//! The AUX mixers can also take the output of another mixer, so AUXOUT1 and
+//! AUXOUT2 can carry the main mix.
+//!
+//! An [`OutputRoute`] lists which sources should reach which outputs, and
+//! [`Codec::set_output_routing`] works out the mixer settings. Because the
+//! headphone and loudspeaker outputs on one side share a mixer, they always
+//! carry the same mix.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::routing::{OutputRoute, OutputSink, OutputSource};
+//! let route = OutputRoute::new()
+//!     .connect(OutputSource::LeftDac, OutputSink::LeftHeadphone)
+//!     .connect(OutputSource::RightDac, OutputSink::RightHeadphone)
+//!     .connect(OutputSource::LeftDac, OutputSink::Aux2);
+//! codec.set_output_routing(&route)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each ADC is fed from an input front end: a PGA (taking the MICP, MICN
+//! and LIN pins) followed by a Mix/Boost stage (taking the PGA output, and
+//! the LIN and AUXIN pins directly). [`Codec::set_input`] sets up a whole
+//! front end from an [`InputSource`].
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::{routing::InputSource, volume::Channel};
+//! codec.set_input(Channel::Left, InputSource::DifferentialMic, 20.0)?;
+//! codec.set_input(Channel::Right, InputSource::LineIn, 0.0)?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    volume::{BoostInput, Channel, Decibels, GainRange, VolumeStage},
+    Codec, Error, Interface, Register,
+};
+
+/// The signals which can be sent to the analog outputs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutputSource {
+    /// The left DAC
+    LeftDac,
+    /// The right DAC
+    RightDac,
+    /// The LAUXIN analog input
+    LeftAux,
+    /// The RAUXIN analog input
+    RightAux,
+    /// The left ADC Mix/Boost stage output, bypassing the ADC
+    LeftBypass,
+    /// The right ADC Mix/Boost stage output, bypassing the ADC
+    RightBypass,
+    /// The output of the left main mixer (LMIX)
+    LeftMixer,
+    /// The output of the right main mixer (RMIX)
+    RightMixer,
+    /// The output of the AUX1 mixer
+    Aux1Mixer,
+}
+
+impl OutputSource {
+    /// This source's bit in a set of sources
+    const fn bit(self) -> u16 {
+        1 << (self as u8)
+    }
+}
+
+/// The analog outputs
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutputSink {
+    /// The LHP headphone output, fed by the left main mixer
+    LeftHeadphone,
+    /// The RHP headphone output, fed by the right main mixer
+    RightHeadphone,
+    /// The LSPKOUT loudspeaker output, fed by the left main mixer
+    LeftSpeaker,
+    /// The RSPKOUT loudspeaker output, fed by the right main mixer
+    RightSpeaker,
+    /// The AUXOUT1 output, fed by the AUX1 mixer
+    Aux1,
+    /// The AUXOUT2 output, fed by the AUX2 mixer
+    Aux2,
+}
+
+impl OutputSink {
+    /// This sink's bit in a set of sinks
+    const fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    /// Which mixer feeds this output
+    const fn mixer(self) -> usize {
+        match self {
+            OutputSink::LeftHeadphone | OutputSink::LeftSpeaker => OutputRoute::LMIX,
+            OutputSink::RightHeadphone | OutputSink::RightSpeaker => OutputRoute::RMIX,
+            OutputSink::Aux1 => OutputRoute::AUX1,
+            OutputSink::Aux2 => OutputRoute::AUX2,
+        }
+    }
+}
+
+/// Describes which sources are connected to which analog outputs
+///
+/// Outputs with nothing connected are powered down by
+/// [`Codec::set_output_routing`].
+///
+/// Not every source can reach every output:
+///
+/// * LMIX takes the left DAC, right DAC, LAUXIN and left bypass
+/// * RMIX takes the right DAC, left DAC, RAUXIN and right bypass
+/// * The AUX1 mixer takes the left DAC, right DAC, right bypass, LMIX and
+///   RMIX
+/// * The AUX2 mixer takes the left DAC, left bypass, LMIX and the AUX1
+///   mixer
+///
+/// ```
+/// use nau88c22::routing::{OutputRoute, OutputSink, OutputSource};
+/// let route = OutputRoute::new().connect(OutputSource::LeftAux, OutputSink::LeftSpeaker);
+/// assert!(route.is_possible());
+/// // LAUXIN can reach AUXOUT2 through LMIX
+/// let chained = route.connect(OutputSource::LeftMixer, OutputSink::Aux2);
+/// assert!(chained.is_possible());
+/// let route = route.connect(OutputSource::LeftAux, OutputSink::Aux1);
+/// assert!(!route.is_possible());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OutputRoute {
+    /// The set of sources going into each mixer
+    mixers: [u16; 4],
+    /// The set of outputs in use
+    sinks: u8,
+    /// The gain from LAUXIN and RAUXIN into the main mixers
+    aux_gain_db: [f32; 2],
+    /// The gain from the left and right bypass into the main mixers
+    bypass_gain_db: [f32; 2],
+    /// Feed RSPKOUT through the right speaker submixer
+    speaker_submixer: bool,
+}
+
+impl OutputRoute {
+    const LMIX: usize = 0;
+    const RMIX: usize = 1;
+    const AUX1: usize = 2;
+    const AUX2: usize = 3;
+
+    /// The sources each mixer can take
+    const MIXER_INPUTS: [u16; 4] = [
+        OutputSource::LeftDac.bit()
+            | OutputSource::RightDac.bit()
+            | OutputSource::LeftAux.bit()
+            | OutputSource::LeftBypass.bit(),
+        OutputSource::RightDac.bit()
+            | OutputSource::LeftDac.bit()
+            | OutputSource::RightAux.bit()
+            | OutputSource::RightBypass.bit(),
+        OutputSource::LeftDac.bit()
+            | OutputSource::RightDac.bit()
+            | OutputSource::RightBypass.bit()
+            | OutputSource::LeftMixer.bit()
+            | OutputSource::RightMixer.bit(),
+        OutputSource::LeftDac.bit()
+            | OutputSource::LeftBypass.bit()
+            | OutputSource::LeftMixer.bit()
+            | OutputSource::Aux1Mixer.bit(),
+    ];
+
+    /// The gains available from the AUX inputs and bypass paths into the
+    /// main mixers
+    pub const MIXER_GAIN: GainRange = GainRange {
+        min_code: 0,
+        max_code: 7,
+        min_db: -15.0,
+        step_db: 3.0,
+    };
+
+    /// Describe a CODEC with every output disconnected
+    pub const fn new() -> OutputRoute {
+        OutputRoute {
+            mixers: [0; 4],
+            sinks: 0,
+            aux_gain_db: [0.0; 2],
+            bypass_gain_db: [0.0; 2],
+            speaker_submixer: false,
+        }
+    }
+
+    /// Connect a source to an output.
+    ///
+    /// This also connects the source to any other output fed by the same
+    /// mixer, but only outputs named here are powered.
+    pub const fn connect(mut self, source: OutputSource, sink: OutputSink) -> OutputRoute {
+        self.mixers[sink.mixer()] |= source.bit();
+        self.sinks |= sink.bit();
+        self
+    }
+
+    /// Set the gain from an AUX input into its main mixer, from -15 dB to
+    /// +6 dB in 3 dB steps (default 0 dB)
+    pub const fn aux_gain_db(mut self, channel: Channel, db: f32) -> OutputRoute {
+        self.aux_gain_db[channel as usize] = db;
+        self
+    }
+
+    /// Set the gain from a bypass path into its main mixer, from -15 dB to
+    /// +6 dB in 3 dB steps (default 0 dB)
+    pub const fn bypass_gain_db(mut self, channel: Channel, db: f32) -> OutputRoute {
+        self.bypass_gain_db[channel as usize] = db;
+        self
+    }
+
+    /// Feed RSPKOUT from the right speaker submixer, which inverts the RMIX
+    /// signal - for driving a bridge-tied speaker across LSPKOUT and
+    /// RSPKOUT.
+    pub const fn speaker_submixer(self, speaker_submixer: bool) -> OutputRoute {
+        OutputRoute {
+            speaker_submixer,
+            ..self
+        }
+    }
+
+    /// Does every connection have a path through the mixers?
+    pub fn is_possible(&self) -> bool {
+        self.mixers
+            .iter()
+            .zip(Self::MIXER_INPUTS)
+            .all(|(sources, allowed)| sources & !allowed == 0)
+    }
+
+    /// Is this source connected to any output?
+    pub fn uses_source(&self, source: OutputSource) -> bool {
+        self.mixers
+            .iter()
+            .any(|sources| sources & source.bit() != 0)
+    }
+
+    /// Is this output in use?
+    pub const fn uses_sink(&self, sink: OutputSink) -> bool {
+        self.sinks & sink.bit() != 0
+    }
+
+    /// Is this source going into this mixer?
+    const fn feeds(&self, mixer: usize, source: OutputSource) -> bool {
+        self.mixers[mixer] & source.bit() != 0
+    }
+
+    /// Convert a main mixer gain to a code
+    fn mixer_gain_code(db: f32) -> Option<u8> {
+        Self::MIXER_GAIN.code_for(db)
+    }
+}
+
+impl Default for OutputRoute {
+    fn default() -> Self {
+        OutputRoute::new()
+    }
+}
+
+/// The ways one channel's input front end can be set up
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InputSource {
+    /// A differential microphone across MICP and MICN, through the PGA
+    DifferentialMic,
+    /// A single-ended microphone on MICN, through the (inverting) PGA, with
+    /// MICP unused
+    SingleEndedMic,
+    /// A line level signal on LIN, straight into the Mix/Boost stage
+    LineIn,
+    /// A line level signal on AUXIN, straight into the Mix/Boost stage
+    AuxIn,
+    /// A differential microphone through the PGA, mixed with LIN and AUXIN
+    /// at 0 dB in the Mix/Boost stage
+    Mixed,
+}
+
+impl InputSource {
+    /// Is the PGA used?
+    pub const fn uses_pga(self) -> bool {
+        matches!(
+            self,
+            InputSource::DifferentialMic | InputSource::SingleEndedMic | InputSource::Mixed
+        )
+    }
+
+    /// The range of the gain given to [`Codec::set_input`]
+    ///
+    /// This is the PGA gain if the PGA is used, or else the Mix/Boost stage
+    /// input gain.
+    pub const fn gain_range(self) -> GainRange {
+        match self {
+            InputSource::LineIn => BoostInput::Line.range(),
+            InputSource::AuxIn => BoostInput::Aux.range(),
+            _ => VolumeStage::Pga.range(),
+        }
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set up one channel's input front end.
+    ///
+    /// Connects the PGA inputs, sets the PGA or Mix/Boost gain to `gain_db`
+    /// (see [`InputSource::gain_range`]), mutes whichever paths are not in
+    /// use, and powers the PGA and Mix/Boost stage as needed. Microphone
+    /// sources also turn on the microphone bias, but it is never turned off
+    /// here, as both channels share it. The ADC itself is powered by
+    /// [`Codec::power_up`].
+    ///
+    /// Returns the gain actually applied, or [`Error::OutOfRange`].
+    pub fn set_input(
+        &mut self,
+        channel: Channel,
+        source: InputSource,
+        gain_db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        let range = source.gain_range();
+        let code = range.code_for(gain_db).ok_or(Error::OutOfRange)?;
+        // Code 5 is 0 dB into the Mix/Boost stage
+        let unity = 5;
+        let (micp, micn) = match source {
+            InputSource::DifferentialMic | InputSource::Mixed => (true, true),
+            InputSource::SingleEndedMic => (false, true),
+            InputSource::LineIn | InputSource::AuxIn => (false, false),
+        };
+        let (line, aux) = match source {
+            InputSource::LineIn => (code, 0),
+            InputSource::AuxIn => (0, code),
+            InputSource::Mixed => (unity, unity),
+            InputSource::DifferentialMic | InputSource::SingleEndedMic => (0, 0),
+        };
+        let pga = source.uses_pga();
+
+        self.modify_inputcontrol(|mut w| {
+            match channel {
+                Channel::Left => {
+                    w.lmicplpga_set(micp);
+                    w.lmicnlpga_set(micn);
+                    w.llinlpga_set(false);
+                }
+                Channel::Right => {
+                    w.rmicprpga_set(micp);
+                    w.rmicnrpga_set(micn);
+                    w.rlinrpga_set(false);
+                }
+            }
+            w
+        })?;
+        // Gain code and mute bit, latched with the update bit
+        self.modify_register(VolumeStage::Pga.register(channel), |value| {
+            if pga {
+                (value & !0x07F) | u16::from(code) | 0x100
+            } else {
+                value | 0x040 | 0x100
+            }
+        })?;
+        self.modify_register(
+            match channel {
+                Channel::Left => Register::LeftADCBoost,
+                Channel::Right => Register::RightADCBoost,
+            },
+            |value| (value & !0x077) | (u16::from(line) << 4) | u16::from(aux),
+        )?;
+        self.modify_powermanagement2(|mut w| {
+            match channel {
+                Channel::Left => {
+                    w.lpgaen_set(pga);
+                    w.lbsten_set(true);
+                }
+                Channel::Right => {
+                    w.rpgaen_set(pga);
+                    w.rbsten_set(true);
+                }
+            }
+            w
+        })?;
+        if pga {
+            self.modify_powermanagement1(|mut w| {
+                w.micbiasen_set(true);
+                w
+            })?;
+        }
+        Ok(range.decibels(code))
+    }
+
+    /// Set up the output mixers, and power exactly the output blocks that
+    /// the route needs.
+    ///
+    /// Checks the route is possible, and the gains are in range, before
+    /// writing anything - otherwise returns [`Error::NoRoute`] or
+    /// [`Error::OutOfRange`]. The DACs, main mixers, AUX mixers and output
+    /// drivers are powered up or down to suit - in particular, a DAC that
+    /// the route does not use is powered down, so playback through it stops.
+    /// A mixer is powered if its output drives an output pin or another
+    /// mixer. The bypass paths also need the ADC Mix/Boost stages powered,
+    /// which is part of the input configuration.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::routing::{OutputRoute, OutputSink, OutputSource};
+    /// let mut codec = nau88c22::Codec::new(MockI2c { registers: [0; 0x50], writes: Vec::new() });
+    /// // The left DAC to LHP, and the same mix out of AUXOUT2
+    /// let route = OutputRoute::new()
+    ///     .connect(OutputSource::LeftDac, OutputSink::LeftHeadphone)
+    ///     .connect(OutputSource::LeftMixer, OutputSink::Aux2);
+    /// codec.set_output_routing(&route).unwrap();
+    /// let i2c = codec.release();
+    /// // LMIX into the AUX2 mixer
+    /// assert_eq!(i2c.registers[0x38], 0x002);
+    /// // The AUX2 mixer powered
+    /// assert_eq!(i2c.registers[0x01], 0x040);
+    /// // The left DAC, LMIX and AUXOUT2 powered
+    /// assert_eq!(i2c.registers[0x03], 0x085);
+    /// ```
+    pub fn set_output_routing(&mut self, route: &OutputRoute) -> Result<(), Error<I::Error>> {
+        use OutputRoute as R;
+        use OutputSource as S;
+        if !route.is_possible() {
+            return Err(Error::NoRoute);
+        }
+        let mut aux_gain = [0u8; 2];
+        let mut bypass_gain = [0u8; 2];
+        for idx in 0..2 {
+            aux_gain[idx] =
+                OutputRoute::mixer_gain_code(route.aux_gain_db[idx]).ok_or(Error::OutOfRange)?;
+            bypass_gain[idx] =
+                OutputRoute::mixer_gain_code(route.bypass_gain_db[idx]).ok_or(Error::OutOfRange)?;
+        }
+
+        self.modify_leftmixer(|mut w| {
+            w.ldaclmx_set(route.feeds(R::LMIX, S::LeftDac));
+            w.lauxlmx_set(route.feeds(R::LMIX, S::LeftAux));
+            w.lauxmxgain_set(aux_gain[Channel::Left as usize]);
+            w.lbyplmx_set(route.feeds(R::LMIX, S::LeftBypass));
+            w.lbypmxgain_set(bypass_gain[Channel::Left as usize]);
+            w
+        })?;
+        self.modify_rightmixer(|mut w| {
+            w.rdacrmx_set(route.feeds(R::RMIX, S::RightDac));
+            w.rauxrmx_set(route.feeds(R::RMIX, S::RightAux));
+            w.rauxmxgain_set(aux_gain[Channel::Right as usize]);
+            w.rbyprmx_set(route.feeds(R::RMIX, S::RightBypass));
+            w.rbyprmxgain_set(bypass_gain[Channel::Right as usize]);
+            w
+        })?;
+        self.modify_outputcontrol(|mut w| {
+            w.rdaclmx_set(route.feeds(R::LMIX, S::RightDac));
+            w.ldacrmx_set(route.feeds(R::RMIX, S::LeftDac));
+            w
+        })?;
+        self.modify_rightspeakersubmix(|mut w| {
+            w.rsubbyp_set(route.speaker_submixer);
+            w.rmixmut_set(false);
+            w
+        })?;
+        self.modify_aux1mixer(|mut w| {
+            w.ldacaux1_set(route.feeds(R::AUX1, S::LeftDac));
+            w.rdacaux1_set(route.feeds(R::AUX1, S::RightDac));
+            w.radcaux1_set(route.feeds(R::AUX1, S::RightBypass));
+            w.lmixaux1_set(route.feeds(R::AUX1, S::LeftMixer));
+            w.rmixaux1_set(route.feeds(R::AUX1, S::RightMixer));
+            w
+        })?;
+        self.modify_aux2mixer(|mut w| {
+            w.ldacaux2_set(route.feeds(R::AUX2, S::LeftDac));
+            w.ladcaux2_set(route.feeds(R::AUX2, S::LeftBypass));
+            w.lmixaux2_set(route.feeds(R::AUX2, S::LeftMixer));
+            w.aux1mix2_set(route.feeds(R::AUX2, S::Aux1Mixer));
+            w
+        })?;
+
+        let lhp = route.uses_sink(OutputSink::LeftHeadphone);
+        let rhp = route.uses_sink(OutputSink::RightHeadphone);
+        let lspk = route.uses_sink(OutputSink::LeftSpeaker);
+        let rspk = route.uses_sink(OutputSink::RightSpeaker);
+        let aux1 = route.uses_sink(OutputSink::Aux1);
+        let aux2 = route.uses_sink(OutputSink::Aux2);
+        self.modify_powermanagement1(|mut w| {
+            w.aux1mxen_set(aux1 || route.uses_source(S::Aux1Mixer));
+            w.aux2mxen_set(aux2);
+            w
+        })?;
+        self.modify_powermanagement2(|mut w| {
+            w.lhpen_set(lhp);
+            w.rhpen_set(rhp);
+            w
+        })?;
+        self.modify_powermanagement3(|mut w| {
+            w.ldacen_set(route.uses_source(S::LeftDac));
+            w.rdacen_set(route.uses_source(S::RightDac));
+            w.lmixen_set(lhp || lspk || route.uses_source(S::LeftMixer));
+            w.rmixen_set(rhp || rspk || route.uses_source(S::RightMixer));
+            w.lspken_set(lspk);
+            w.rspken_set(rspk);
+            w.auxout1en_set(aux1);
+            w.auxout2en_set(aux2);
+            w
+        })
+    }
+}
+
+// End of file
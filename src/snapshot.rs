@@ -0,0 +1,385 @@
+//! Register snapshots, for capturing and comparing the state of the CODEC
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>, uart: &mut impl core::fmt::Write) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use core::fmt::Write;
+//! let known_good = nau88c22::Snapshot::default();
+//! let snapshot = codec.dump()?;
+//! // Print every register
+//! writeln!(uart, "{}", snapshot).unwrap();
+//! // Print only what has changed since reset
+//! for change in known_good.diff(&snapshot) {
+//!     writeln!(uart, "{}", change).unwrap();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use core::fmt::Debug;
+
+use crate::{registers, Register};
+
+/// The contents of every readable register, at one point in time
+///
+/// Holds the registers in the order given by [`Register::readable`]. The
+/// [`Default`] snapshot holds the power-on value of every register.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    values: [u16; Snapshot::NUM_REGISTERS],
+}
+
+impl Snapshot {
+    /// How many registers are held in a snapshot
+    pub const NUM_REGISTERS: usize = 67;
+
+    /// Create a snapshot from a list of register values.
+    ///
+    /// The values must be in the order given by [`Register::readable`].
+    pub const fn from_values(values: [u16; Snapshot::NUM_REGISTERS]) -> Snapshot {
+        Snapshot { values }
+    }
+
+    /// Get the register values, in the order given by [`Register::readable`].
+    pub fn values(&self) -> &[u16; Snapshot::NUM_REGISTERS] {
+        &self.values
+    }
+
+    /// Get the value of a register, if it is held in the snapshot
+    pub fn get(&self, register: Register) -> Option<u16> {
+        Self::index_of(register).map(|idx| self.values[idx])
+    }
+
+    /// Change the value of a register, if it is held in the snapshot
+    pub fn set(&mut self, register: Register, value: u16) {
+        if let Some(idx) = Self::index_of(register) {
+            self.values[idx] = value & 0x1FF;
+        }
+    }
+
+    /// Go through every register in the snapshot, with its value
+    pub fn iter(&self) -> impl Iterator<Item = (Register, u16)> + '_ {
+        Register::readable()
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+
+    /// Find every register which is different in `other`.
+    ///
+    /// `self` is treated as the *before* state, and `other` as the *after*
+    /// state.
+    ///
+    /// ```
+    /// use nau88c22::{Register, Snapshot};
+    /// let before = Snapshot::default();
+    /// let mut after = before.clone();
+    /// after.set(Register::LHPVolume, 0x030);
+    /// let mut changes = before.diff(&after);
+    /// let change = changes.next().unwrap();
+    /// assert_eq!(change.register, Register::LHPVolume);
+    /// assert_eq!((change.before, change.after), (0x039, 0x030));
+    /// assert!(format!("{:?}", change.decoded_after()).contains("lhpgain: 48"));
+    /// assert!(changes.next().is_none());
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Snapshot) -> impl Iterator<Item = RegisterDiff> + 'a {
+        self.iter()
+            .zip(other.values.iter().copied())
+            .filter(|((_register, before), after)| before != after)
+            .map(|((register, before), after)| RegisterDiff {
+                register,
+                before,
+                after,
+            })
+    }
+
+    /// Get the value to write back to a register when restoring this snapshot.
+    ///
+    /// Read-only and write-only bits are cleared.
+    pub(crate) fn restore_value(&self, register: Register) -> u16 {
+        let value = self.get(register).unwrap_or(register.default_value());
+        value & !register.read_only_mask() & !register.write_only_mask()
+    }
+
+    /// Find where a register lives in the snapshot
+    fn index_of(register: Register) -> Option<usize> {
+        Register::readable().iter().position(|r| *r == register)
+    }
+}
+
+/// Registers restored first - the clocks, the PLL and the digital audio
+/// interface.
+pub(crate) const CLOCK_REGISTERS: &[Register] = &[
+    Register::PllN,
+    Register::PllK1,
+    Register::PllK2,
+    Register::PllK3,
+    Register::ClockControl1,
+    Register::ClockControl2,
+    Register::AudioInterface,
+    Register::Companding,
+    Register::GPIO,
+    Register::Misc,
+    Register::LeftTimeSlot,
+    Register::RightTimeSlot,
+    Register::MiscControls,
+];
+
+/// Registers restored next - everything which doesn't power up a block, or
+/// drive an output, and which has no update bit.
+pub(crate) const SETUP_REGISTERS: &[Register] = &[
+    Register::DACControl,
+    Register::ADCControl,
+    Register::EQ1HighCutoff,
+    Register::EQ2Peak1,
+    Register::EQ3Peak2,
+    Register::EQ4Peak3,
+    Register::EQ5LowCutoff,
+    Register::DACLimiter1,
+    Register::DACLimiter2,
+    Register::ALCControl1,
+    Register::ALCControl2,
+    Register::ALCControl3,
+    Register::NoiseGate,
+    Register::ThreeDControl,
+    Register::DacDither,
+    Register::AlcEnhancements1,
+    Register::AlcEnhancements2,
+    Register::InputControl,
+    Register::LeftADCBoost,
+    Register::RightADCBoost,
+    Register::OutputControl,
+    Register::LeftMixer,
+    Register::RightMixer,
+    Register::RightSpeakerSubmix,
+    Register::PowerManagement,
+    Register::TieOffOverrides,
+    Register::PowerTieOffCtrl,
+    Register::OutputTieOffControl,
+    Register::JackDetect1,
+    Register::JackDetect2,
+    Register::ControlAndStatus,
+];
+
+/// Left/right gain pairs which are latched together, by writing the left
+/// register first and then the right register with its update bit set.
+pub(crate) const GAIN_PAIRS: &[(Register, Register)] = &[
+    (Register::LeftDACVolume, Register::RightDACVolume),
+    (Register::LeftADCVolume, Register::RightADCVolume),
+    (Register::LeftInputPGAGain, Register::RightInputPGAGain),
+];
+
+/// Left/right output volume pairs, latched like [`GAIN_PAIRS`]. Bit 6 is the
+/// mute bit in all of these.
+pub(crate) const OUTPUT_PAIRS: &[(Register, Register)] = &[
+    (Register::LHPVolume, Register::RHPVolume),
+    (Register::LSPKOUTVolume, Register::RSPKOUTVolume),
+];
+
+/// The AUX output mixers. Bit 6 is the mute bit in both of these.
+pub(crate) const AUX_OUTPUTS: &[Register] = &[Register::AUX2Mixer, Register::AUX1Mixer];
+
+/// The notch filter registers, the last of which is written with its update
+/// bit set.
+pub(crate) const NOTCH_REGISTERS: &[Register] = &[
+    Register::NotchFilter1,
+    Register::NotchFilter2,
+    Register::NotchFilter3,
+    Register::NotchFilter4,
+];
+
+/// The power enable registers, restored once everything else is set up.
+pub(crate) const POWER_REGISTERS: &[Register] = &[
+    Register::PowerManagement1,
+    Register::PowerManagement2,
+    Register::PowerManagement3,
+];
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        let mut values = [0u16; Snapshot::NUM_REGISTERS];
+        for (value, register) in values.iter_mut().zip(Register::readable()) {
+            *value = register.default_value();
+        }
+        Snapshot { values }
+    }
+}
+
+impl Debug for Snapshot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries(
+                self.iter()
+                    .map(|(register, value)| Decoded { register, value }),
+            )
+            .finish()
+    }
+}
+
+impl core::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (register, value) in self.iter() {
+            writeln!(
+                f,
+                "R{:02} {:?} = 0x{:03x}: {:?}",
+                register as u8,
+                register,
+                value,
+                Decoded { register, value }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Snapshot {
+    fn format(&self, f: defmt::Formatter) {
+        for (register, value) in self.iter() {
+            defmt::write!(
+                f,
+                "R{=u8} {} = 0x{=u16:03x}\n",
+                register as u8,
+                register,
+                value
+            );
+        }
+    }
+}
+
+/// A register which differs between two [`Snapshot`]s
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterDiff {
+    /// Which register changed
+    pub register: Register,
+    /// The value in the first snapshot
+    pub before: u16,
+    /// The value in the second snapshot
+    pub after: u16,
+}
+
+impl RegisterDiff {
+    /// Which bits changed
+    pub fn changed_bits(&self) -> u16 {
+        self.before ^ self.after
+    }
+
+    /// The value in the first snapshot, which `Debug` prints field by field
+    pub fn decoded_before(&self) -> Decoded {
+        Decoded {
+            register: self.register,
+            value: self.before,
+        }
+    }
+
+    /// The value in the second snapshot, which `Debug` prints field by field
+    pub fn decoded_after(&self) -> Decoded {
+        Decoded {
+            register: self.register,
+            value: self.after,
+        }
+    }
+}
+
+impl core::fmt::Display for RegisterDiff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            f,
+            "R{:02} {:?}: 0x{:03x} -> 0x{:03x}",
+            self.register as u8, self.register, self.before, self.after
+        )?;
+        writeln!(f, "  before: {:?}", self.decoded_before())?;
+        writeln!(f, "  after:  {:?}", self.decoded_after())
+    }
+}
+
+/// A raw register value, which `Debug` prints using the matching type from
+/// [`registers`]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    /// The register this value came from
+    pub register: Register,
+    /// The raw nine-bit value
+    pub value: u16,
+}
+
+impl Debug for Decoded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.register {
+            Register::SoftwareReset => write!(f, "SoftwareReset(0x{:03x})", self.value),
+            Register::PowerManagement1 => registers::PowerManagement1(self.value).fmt(f),
+            Register::PowerManagement2 => registers::PowerManagement2(self.value).fmt(f),
+            Register::PowerManagement3 => registers::PowerManagement3(self.value).fmt(f),
+            Register::AudioInterface => registers::AudioInterface(self.value).fmt(f),
+            Register::Companding => registers::Companding(self.value).fmt(f),
+            Register::ClockControl1 => registers::ClockControl1(self.value).fmt(f),
+            Register::ClockControl2 => registers::ClockControl2(self.value).fmt(f),
+            Register::GPIO => registers::GPIO(self.value).fmt(f),
+            Register::JackDetect1 => registers::JackDetect1(self.value).fmt(f),
+            Register::DACControl => registers::DACControl(self.value).fmt(f),
+            Register::LeftDACVolume => registers::LeftDACVolume(self.value).fmt(f),
+            Register::RightDACVolume => registers::RightDACVolume(self.value).fmt(f),
+            Register::JackDetect2 => registers::JackDetect2(self.value).fmt(f),
+            Register::ADCControl => registers::ADCControl(self.value).fmt(f),
+            Register::LeftADCVolume => registers::LeftADCVolume(self.value).fmt(f),
+            Register::RightADCVolume => registers::RightADCVolume(self.value).fmt(f),
+            Register::EQ1HighCutoff => registers::EQ1HighCutoff(self.value).fmt(f),
+            Register::EQ2Peak1 => registers::EQ2Peak1(self.value).fmt(f),
+            Register::EQ3Peak2 => registers::EQ3Peak2(self.value).fmt(f),
+            Register::EQ4Peak3 => registers::EQ4Peak3(self.value).fmt(f),
+            Register::EQ5LowCutoff => registers::EQ5LowCutoff(self.value).fmt(f),
+            Register::DACLimiter1 => registers::DACLimiter1(self.value).fmt(f),
+            Register::DACLimiter2 => registers::DACLimiter2(self.value).fmt(f),
+            Register::NotchFilter1 => registers::NotchFilter1(self.value).fmt(f),
+            Register::NotchFilter2 => registers::NotchFilter2(self.value).fmt(f),
+            Register::NotchFilter3 => registers::NotchFilter3(self.value).fmt(f),
+            Register::NotchFilter4 => registers::NotchFilter4(self.value).fmt(f),
+            Register::ALCControl1 => registers::ALCControl1(self.value).fmt(f),
+            Register::ALCControl2 => registers::ALCControl2(self.value).fmt(f),
+            Register::ALCControl3 => registers::ALCControl3(self.value).fmt(f),
+            Register::NoiseGate => registers::NoiseGate(self.value).fmt(f),
+            Register::PllN => registers::PllN(self.value).fmt(f),
+            Register::PllK1 => registers::PllK1(self.value).fmt(f),
+            Register::PllK2 => registers::PllK2(self.value).fmt(f),
+            Register::PllK3 => registers::PllK3(self.value).fmt(f),
+            Register::ThreeDControl => registers::ThreeDControl(self.value).fmt(f),
+            Register::RightSpeakerSubmix => registers::RightSpeakerSubmix(self.value).fmt(f),
+            Register::InputControl => registers::InputControl(self.value).fmt(f),
+            Register::LeftInputPGAGain => registers::LeftInputPGAGain(self.value).fmt(f),
+            Register::RightInputPGAGain => registers::RightInputPGAGain(self.value).fmt(f),
+            Register::LeftADCBoost => registers::LeftADCBoost(self.value).fmt(f),
+            Register::RightADCBoost => registers::RightADCBoost(self.value).fmt(f),
+            Register::OutputControl => registers::OutputControl(self.value).fmt(f),
+            Register::LeftMixer => registers::LeftMixer(self.value).fmt(f),
+            Register::RightMixer => registers::RightMixer(self.value).fmt(f),
+            Register::LHPVolume => registers::LHPVolume(self.value).fmt(f),
+            Register::RHPVolume => registers::RHPVolume(self.value).fmt(f),
+            Register::LSPKOUTVolume => registers::LSPKOUTVolume(self.value).fmt(f),
+            Register::RSPKOUTVolume => registers::RSPKOUTVolume(self.value).fmt(f),
+            Register::AUX2Mixer => registers::AUX2Mixer(self.value).fmt(f),
+            Register::AUX1Mixer => registers::AUX1Mixer(self.value).fmt(f),
+            Register::PowerManagement => registers::PowerManagement(self.value).fmt(f),
+            Register::LeftTimeSlot => registers::LeftTimeSlot(self.value).fmt(f),
+            Register::Misc => registers::Misc(self.value).fmt(f),
+            Register::RightTimeSlot => registers::RightTimeSlot(self.value).fmt(f),
+            Register::DeviceRevisionNo => registers::DeviceRevisionNo(self.value).fmt(f),
+            Register::DeviceId => registers::DeviceId(self.value).fmt(f),
+            Register::DacDither => registers::DacDither(self.value).fmt(f),
+            Register::AlcEnhancements1 => registers::AlcEnhancements1(self.value).fmt(f),
+            Register::AlcEnhancements2 => registers::AlcEnhancements2(self.value).fmt(f),
+            Register::MiscControls => registers::MiscControls(self.value).fmt(f),
+            Register::TieOffOverrides => registers::TieOffOverrides(self.value).fmt(f),
+            Register::PowerTieOffCtrl => registers::PowerTieOffCtrl(self.value).fmt(f),
+            Register::P2PDetectorRead => registers::P2PDetectorRead(self.value).fmt(f),
+            Register::PeakDetectorRead => registers::PeakDetectorRead(self.value).fmt(f),
+            Register::ControlAndStatus => registers::ControlAndStatus(self.value).fmt(f),
+            Register::OutputTieOffControl => registers::OutputTieOffControl(self.value).fmt(f),
+        }
+    }
+}
+
+// End of file
@@ -0,0 +1,70 @@
+//! Helpers shared by the feature modules
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{clocks::ClockConfig, Codec, Error, Interface};
+
+/// Find the time in a table (in µs) closest to the one given, on a log
+/// scale, and return its index.
+///
+/// A zero entry is only chosen if the time is closer to zero than to the
+/// next entry.
+pub(crate) fn time_code(table: &[u32], target_us: f32) -> u8 {
+    let distance = |us: u32| {
+        if us == 0 {
+            return if target_us < table[1] as f32 / 2.0 {
+                0.0
+            } else {
+                f32::INFINITY
+            };
+        }
+        libm::fabsf(libm::logf(us as f32 / target_us))
+    };
+    let mut best = 0;
+    for (idx, us) in table.iter().enumerate() {
+        if distance(*us) < distance(table[best]) {
+            best = idx;
+        }
+    }
+    best as u8
+}
+
+/// Find the level in a table (in dB) closest to the one given, and return
+/// its index.
+///
+/// The table must be evenly spaced, but may go up or down. Returns `None` if
+/// the level is more than half a step beyond either end, or is `NaN`.
+pub(crate) fn level_code(table: &[f32], db: f32) -> Option<u8> {
+    let distance = |level: f32| libm::fabsf(level - db);
+    let mut best = 0;
+    for (idx, level) in table.iter().enumerate() {
+        if distance(*level) < distance(table[best]) {
+            best = idx;
+        }
+    }
+    if distance(table[best]) <= libm::fabsf(table[1] - table[0]) / 2.0 {
+        Some(best as u8)
+    } else {
+        None
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// The sample rate set in [`registers::ClockControl2::smplr`], in Hz.
+    ///
+    /// [`registers::ClockControl2::smplr`]: crate::registers::ClockControl2::smplr
+    pub(crate) fn sample_rate_hint_hz(&mut self) -> Result<u32, Error<I::Error>> {
+        let smplr = self.read_clockcontrol2()?.smplr();
+        Ok(ClockConfig::SAMPLE_RATE_HINTS
+            .get(usize::from(smplr))
+            .copied()
+            .unwrap_or(ClockConfig::SAMPLE_RATE_HINTS[0]))
+    }
+}
+
+// End of file
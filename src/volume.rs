@@ -0,0 +1,790 @@
+//! Gain settings in decibels
+//!
+//! Every gain stage on the NAU88C22 uses a different code: the DAC and ADC
+//! use 0.5 dB steps from -127 dB, the input PGA uses 0.75 dB steps from
+//! -12 dB, the headphone and loudspeaker outputs use 1 dB steps from -57 dB,
+//! and the boost stage inputs use 3 dB steps from -12 dB. The functions here
+//! convert to and from decibels, rounding to the nearest legal setting.
+//!
+//! ```rust,no_run
+//! # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+//! use nau88c22::volume::Channel;
+//! let applied = codec.set_headphone_volume_db(Channel::Left, -10.4)?;
+//! assert_eq!(applied.db(), -10.0);
+//! // The DAC can be muted completely
+//! codec.set_dac_volume_db(Channel::Right, f32::NEG_INFINITY)?;
+//! # Ok(())
+//! # }
+//! ```
+
+// SPDX-FileCopyrightText: 2023 Jonathan 'theJPster' Pallant <github@thejpster.org.uk>
+//
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{Codec, Error, Interface, Register};
+
+/// Selects the left or right channel of a stereo pair
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Channel {
+    /// The left channel
+    Left,
+    /// The right channel
+    Right,
+}
+
+/// A gain, in decibels
+///
+/// A gain of `f32::NEG_INFINITY` means the stage is muted, or the path is
+/// disconnected.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Decibels(pub f32);
+
+impl Decibels {
+    /// Muted, or disconnected
+    pub const MUTE: Decibels = Decibels(f32::NEG_INFINITY);
+
+    /// The gain in decibels
+    pub const fn db(self) -> f32 {
+        self.0
+    }
+
+    /// Is this a mute setting?
+    pub fn is_mute(self) -> bool {
+        self.0 == f32::NEG_INFINITY
+    }
+}
+
+impl From<f32> for Decibels {
+    fn from(value: f32) -> Self {
+        Decibels(value)
+    }
+}
+
+impl core::fmt::Display for Decibels {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_mute() {
+            write!(f, "mute")
+        } else {
+            write!(f, "{:+.2} dB", self.0)
+        }
+    }
+}
+
+/// Describes the legal codes for a gain field
+///
+/// Code `min_code` gives `min_db`, and each code above that adds `step_db`.
+/// If `min_code` is `1`, then code `0` means muted (or disconnected).
+///
+/// ```
+/// use nau88c22::volume::{Decibels, VolumeStage};
+/// let range = VolumeStage::Pga.range();
+/// assert_eq!(range.code_for(0.0), Some(16));
+/// // Rounds to the nearest 0.75 dB step
+/// assert_eq!(range.code_for(1.0), Some(17));
+/// assert_eq!(range.decibels(17), Decibels(0.75));
+/// assert_eq!(range.code_for(40.0), None);
+/// // The PGA has a separate mute bit, rather than a mute code
+/// assert_eq!(range.code_for(f32::NEG_INFINITY), None);
+///
+/// let range = VolumeStage::Dac.range();
+/// assert_eq!(range.code_for(f32::NEG_INFINITY), Some(0));
+/// assert_eq!(range.code_for(-127.2), Some(1));
+/// assert_eq!(range.decibels(255), Decibels(0.0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GainRange {
+    /// The lowest code which is not a mute setting
+    pub min_code: u8,
+    /// The highest code
+    pub max_code: u8,
+    /// The gain, in decibels, at `min_code`
+    pub min_db: f32,
+    /// The gain, in decibels, added by each code above `min_code`
+    pub step_db: f32,
+}
+
+impl GainRange {
+    /// The gain at `max_code`
+    pub fn max_db(&self) -> f32 {
+        self.min_db + f32::from(self.max_code - self.min_code) * self.step_db
+    }
+
+    /// Does code `0` mean muted?
+    pub fn has_mute(&self) -> bool {
+        self.min_code > 0
+    }
+
+    /// Convert a code to a gain.
+    ///
+    /// Codes above `max_code` are treated as `max_code`.
+    pub fn decibels(&self, code: u8) -> Decibels {
+        if code < self.min_code {
+            Decibels::MUTE
+        } else {
+            let code = code.min(self.max_code);
+            Decibels(self.min_db + f32::from(code - self.min_code) * self.step_db)
+        }
+    }
+
+    /// Find the code with the gain closest to the one given.
+    ///
+    /// Returns `None` if the gain is more than half a step outside the range,
+    /// or is `NaN`. A gain of `f32::NEG_INFINITY` gives code `0` if this
+    /// stage can be muted.
+    pub fn code_for(&self, db: f32) -> Option<u8> {
+        if db == f32::NEG_INFINITY && self.has_mute() {
+            return Some(0);
+        }
+        let steps = (db - self.min_db) / self.step_db;
+        if steps.is_nan() || steps < -0.5 || steps > f32::from(self.max_code - self.min_code) + 0.5
+        {
+            return None;
+        }
+        // `steps` is at least -0.5, so truncation rounds to nearest here
+        let steps = (steps + 0.5) as u8;
+        Some((self.min_code + steps).min(self.max_code))
+    }
+}
+
+/// The gain stages which come in left/right pairs with an update bit
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VolumeStage {
+    /// DAC digital volume ([`registers::LeftDACVolume::ldacgain`])
+    ///
+    /// [`registers::LeftDACVolume::ldacgain`]: crate::registers::LeftDACVolume::ldacgain
+    Dac,
+    /// ADC digital volume ([`registers::LeftADCVolume::ladcgain`])
+    ///
+    /// [`registers::LeftADCVolume::ladcgain`]: crate::registers::LeftADCVolume::ladcgain
+    Adc,
+    /// Input PGA gain ([`registers::LeftInputPGAGain::lpgagain`])
+    ///
+    /// [`registers::LeftInputPGAGain::lpgagain`]: crate::registers::LeftInputPGAGain::lpgagain
+    Pga,
+    /// Headphone output volume ([`registers::LHPVolume::lhpgain`])
+    ///
+    /// [`registers::LHPVolume::lhpgain`]: crate::registers::LHPVolume::lhpgain
+    Headphone,
+    /// Loudspeaker output volume ([`registers::LSPKOUTVolume::lspkgain`])
+    ///
+    /// [`registers::LSPKOUTVolume::lspkgain`]: crate::registers::LSPKOUTVolume::lspkgain
+    Speaker,
+}
+
+impl VolumeStage {
+    /// The legal codes for this stage
+    pub const fn range(self) -> GainRange {
+        match self {
+            VolumeStage::Dac | VolumeStage::Adc => GainRange {
+                min_code: 1,
+                max_code: 255,
+                min_db: -127.0,
+                step_db: 0.5,
+            },
+            VolumeStage::Pga => GainRange {
+                min_code: 0,
+                max_code: 63,
+                min_db: -12.0,
+                step_db: 0.75,
+            },
+            VolumeStage::Headphone | VolumeStage::Speaker => GainRange {
+                min_code: 0,
+                max_code: 63,
+                min_db: -57.0,
+                step_db: 1.0,
+            },
+        }
+    }
+
+    /// The register holding the gain for one channel of this stage
+    pub const fn register(self, channel: Channel) -> Register {
+        match (self, channel) {
+            (VolumeStage::Dac, Channel::Left) => Register::LeftDACVolume,
+            (VolumeStage::Dac, Channel::Right) => Register::RightDACVolume,
+            (VolumeStage::Adc, Channel::Left) => Register::LeftADCVolume,
+            (VolumeStage::Adc, Channel::Right) => Register::RightADCVolume,
+            (VolumeStage::Pga, Channel::Left) => Register::LeftInputPGAGain,
+            (VolumeStage::Pga, Channel::Right) => Register::RightInputPGAGain,
+            (VolumeStage::Headphone, Channel::Left) => Register::LHPVolume,
+            (VolumeStage::Headphone, Channel::Right) => Register::RHPVolume,
+            (VolumeStage::Speaker, Channel::Left) => Register::LSPKOUTVolume,
+            (VolumeStage::Speaker, Channel::Right) => Register::RSPKOUTVolume,
+        }
+    }
+
+    /// Does this stage have a zero-cross detector?
+    ///
+    /// The DAC and ADC do not.
+    pub const fn has_zero_cross(self) -> bool {
+        matches!(
+            self,
+            VolumeStage::Pga | VolumeStage::Headphone | VolumeStage::Speaker
+        )
+    }
+
+    /// The bits holding the gain code
+    const fn gain_mask(self) -> u16 {
+        match self {
+            VolumeStage::Dac | VolumeStage::Adc => 0x0FF,
+            VolumeStage::Pga | VolumeStage::Headphone | VolumeStage::Speaker => 0x03F,
+        }
+    }
+}
+
+/// The inputs to the ADC boost stage
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BoostInput {
+    /// The line input ([`registers::LeftADCBoost::lpgabstgain`])
+    ///
+    /// [`registers::LeftADCBoost::lpgabstgain`]: crate::registers::LeftADCBoost::lpgabstgain
+    Line,
+    /// The auxiliary input ([`registers::LeftADCBoost::lauxbstgain`])
+    ///
+    /// [`registers::LeftADCBoost::lauxbstgain`]: crate::registers::LeftADCBoost::lauxbstgain
+    Aux,
+}
+
+impl BoostInput {
+    /// The legal codes for this input. Code `0` disconnects the input.
+    pub const fn range(self) -> GainRange {
+        GainRange {
+            min_code: 1,
+            max_code: 7,
+            min_db: -12.0,
+            step_db: 3.0,
+        }
+    }
+
+    /// The position of the gain code in the ADC Boost registers
+    const fn shift(self) -> u16 {
+        match self {
+            BoostInput::Line => 4,
+            BoostInput::Aux => 0,
+        }
+    }
+}
+
+/// Walks a stereo gain stage to a new setting, one step at a time
+///
+/// Each call to [`VolumeRamp::tick`] moves both channels one step closer to
+/// the target, latching them together with the update bits. Nothing blocks,
+/// so you can call it from a timer interrupt. See
+/// [`Codec::start_volume_ramp`], or [`Codec::ramp_volume`] for a blocking
+/// version.
+///
+/// ```rust,no_run
+/// # fn example<I>(codec: &mut nau88c22::Codec<I>) -> Result<(), nau88c22::Error<I::Error>> where I: nau88c22::Interface {
+/// use nau88c22::volume::VolumeStage;
+/// // Fade the loudspeakers down to -40 dB, 2 dB per tick
+/// let mut ramp = codec.start_volume_ramp(VolumeStage::Speaker, -40.0, 2.0)?;
+/// // ... then in your timer interrupt:
+/// if ramp.tick(codec)? {
+///     // the ramp is finished
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VolumeRamp {
+    stage: VolumeStage,
+    left: u8,
+    right: u8,
+    target: u8,
+    step: u8,
+}
+
+impl VolumeRamp {
+    /// Make a ramp from the given codes to the target code, moving `step`
+    /// codes per tick.
+    pub const fn new(stage: VolumeStage, left: u8, right: u8, target: u8, step: u8) -> VolumeRamp {
+        VolumeRamp {
+            stage,
+            left,
+            right,
+            target,
+            step: if step == 0 { 1 } else { step },
+        }
+    }
+
+    /// Which stage this ramp is for
+    pub const fn stage(&self) -> VolumeStage {
+        self.stage
+    }
+
+    /// The gain this ramp finishes at
+    pub fn target(&self) -> Decibels {
+        self.stage.range().decibels(self.target)
+    }
+
+    /// The gains most recently written, as `(left, right)`
+    pub fn current(&self) -> (Decibels, Decibels) {
+        let range = self.stage.range();
+        (range.decibels(self.left), range.decibels(self.right))
+    }
+
+    /// Have both channels reached the target?
+    pub const fn is_finished(&self) -> bool {
+        self.left == self.target && self.right == self.target
+    }
+
+    /// Move both channels one step closer to the target.
+    ///
+    /// Returns `true` once the target has been reached, after which further
+    /// calls do nothing. Each tick moves the gain by the ramp's step, except
+    /// the last, which stops exactly on the target.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::volume::{VolumeRamp, VolumeStage};
+    /// let i2c = MockI2c { registers: [0; 0x50], writes: Vec::new() };
+    /// let mut codec = nau88c22::Codec::new(i2c);
+    /// // From 0 dB (code 57) down to -10 dB (code 47), 3 codes per tick
+    /// let mut ramp = VolumeRamp::new(VolumeStage::Headphone, 57, 57, 47, 3);
+    /// assert_eq!(ramp.tick(&mut codec), Ok(false));
+    /// assert_eq!(ramp.current().0.db(), -3.0);
+    /// assert_eq!(ramp.tick(&mut codec), Ok(false));
+    /// assert_eq!(ramp.tick(&mut codec), Ok(false));
+    /// assert_eq!(ramp.current().0.db(), -9.0);
+    /// assert_eq!(ramp.tick(&mut codec), Ok(true));
+    /// assert_eq!(ramp.current().0.db(), -10.0);
+    /// assert_eq!(ramp.tick(&mut codec), Ok(true));
+    /// let i2c = codec.release();
+    /// let codes: Vec<u16> = i2c.writes.iter().map(|(_register, value)| value & 0x3F).collect();
+    /// assert_eq!(codes, [54, 54, 51, 51, 48, 48, 47, 47]);
+    /// ```
+    pub fn tick<I>(&mut self, codec: &mut Codec<I>) -> Result<bool, Error<I::Error>>
+    where
+        I: Interface,
+    {
+        if self.is_finished() {
+            return Ok(true);
+        }
+        let left = Self::approach(self.left, self.target, self.step);
+        let right = Self::approach(self.right, self.target, self.step);
+        codec.set_stereo_codes(self.stage, left, right)?;
+        self.left = left;
+        self.right = right;
+        Ok(self.is_finished())
+    }
+
+    /// Move `code` up to `step` codes towards `target`
+    const fn approach(code: u8, target: u8, step: u8) -> u8 {
+        if code < target {
+            let next = code.saturating_add(step);
+            if next > target {
+                target
+            } else {
+                next
+            }
+        } else {
+            let next = code.saturating_sub(step);
+            if next < target {
+                target
+            } else {
+                next
+            }
+        }
+    }
+}
+
+impl<I> Codec<I>
+where
+    I: Interface,
+{
+    /// Set the gain of one channel of a stage, in decibels.
+    ///
+    /// The gain is rounded to the nearest legal setting, and takes effect
+    /// immediately (subject to zero-cross detection). Returns the gain
+    /// actually applied, or [`Error::OutOfRange`] if the gain is more than
+    /// half a step outside the range of the stage.
+    pub fn set_volume_db(
+        &mut self,
+        stage: VolumeStage,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        let range = stage.range();
+        let code = range.code_for(db).ok_or(Error::OutOfRange)?;
+        let mask = stage.gain_mask();
+        self.modify_register(stage.register(channel), |value| {
+            (value & !mask) | u16::from(code) | 0x100
+        })?;
+        Ok(range.decibels(code))
+    }
+
+    /// Get the gain of one channel of a stage, in decibels.
+    pub fn volume_db(
+        &mut self,
+        stage: VolumeStage,
+        channel: Channel,
+    ) -> Result<Decibels, Error<I::Error>> {
+        let value = self.read_register(stage.register(channel))?;
+        Ok(stage.range().decibels((value & stage.gain_mask()) as u8))
+    }
+
+    /// Set the gain of both channels of a stage, in decibels, so that they
+    /// change at the same moment.
+    ///
+    /// The left channel is written with its update bit clear, so the chip
+    /// holds it back, and then the right channel is written with its update
+    /// bit set, which makes both values take effect together. Both gains are
+    /// checked before anything is written. Returns the gains actually
+    /// applied, as `(left, right)`.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::volume::VolumeStage;
+    /// let mut i2c = MockI2c { registers: [0; 0x50], writes: Vec::new() };
+    /// // Both headphone channels at 0 dB
+    /// i2c.registers[0x34] = 0x039;
+    /// i2c.registers[0x35] = 0x039;
+    /// let mut codec = nau88c22::Codec::new(i2c);
+    /// codec.set_zero_cross(VolumeStage::Headphone, true).unwrap();
+    /// let (left, right) = codec.set_stereo_volume(VolumeStage::Headphone, -20.0, -18.0).unwrap();
+    /// assert_eq!((left.db(), right.db()), (-20.0, -18.0));
+    /// let i2c = codec.release();
+    /// // Only the right channel has its update bit set
+    /// assert_eq!(
+    ///     i2c.writes,
+    ///     [(0x34, 0x0B9), (0x35, 0x1B9), (0x34, 0x0A5), (0x35, 0x1A7)]
+    /// );
+    /// ```
+    pub fn set_stereo_volume(
+        &mut self,
+        stage: VolumeStage,
+        left_db: f32,
+        right_db: f32,
+    ) -> Result<(Decibels, Decibels), Error<I::Error>> {
+        let range = stage.range();
+        let left = range.code_for(left_db).ok_or(Error::OutOfRange)?;
+        let right = range.code_for(right_db).ok_or(Error::OutOfRange)?;
+        self.set_stereo_codes(stage, left, right)?;
+        Ok((range.decibels(left), range.decibels(right)))
+    }
+
+    /// Write raw gain codes to both channels of a stage, latching them
+    /// together with the update bit on the right channel.
+    pub(crate) fn set_stereo_codes(
+        &mut self,
+        stage: VolumeStage,
+        left: u8,
+        right: u8,
+    ) -> Result<(), Error<I::Error>> {
+        let mask = stage.gain_mask();
+        self.modify_register(stage.register(Channel::Left), |value| {
+            (value & !mask) | u16::from(left)
+        })?;
+        self.modify_register(stage.register(Channel::Right), |value| {
+            (value & !mask) | u16::from(right) | 0x100
+        })
+    }
+
+    /// Turn the zero-cross detector for both channels of a stage on or off.
+    ///
+    /// When on, gain changes wait until the signal crosses zero, which makes
+    /// them quieter. Does nothing for stages without a zero-cross detector
+    /// (see [`VolumeStage::has_zero_cross`]). Like the gains, both channels
+    /// change together.
+    pub fn set_zero_cross(
+        &mut self,
+        stage: VolumeStage,
+        enable: bool,
+    ) -> Result<(), Error<I::Error>> {
+        if !stage.has_zero_cross() {
+            return Ok(());
+        }
+        let zero_cross = |value: u16| {
+            if enable {
+                value | 0x080
+            } else {
+                value & !0x080
+            }
+        };
+        self.modify_register(stage.register(Channel::Left), zero_cross)?;
+        self.modify_register(stage.register(Channel::Right), |value| {
+            zero_cross(value) | 0x100
+        })
+    }
+
+    /// Set the DAC digital volume, from -127 dB to 0 dB in 0.5 dB steps.
+    ///
+    /// Give `f32::NEG_INFINITY` to mute. See [`Codec::set_volume_db`].
+    pub fn set_dac_volume_db(
+        &mut self,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        self.set_volume_db(VolumeStage::Dac, channel, db)
+    }
+
+    /// Set the ADC digital volume, from -127 dB to 0 dB in 0.5 dB steps.
+    ///
+    /// Give `f32::NEG_INFINITY` to mute. See [`Codec::set_volume_db`].
+    pub fn set_adc_volume_db(
+        &mut self,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        self.set_volume_db(VolumeStage::Adc, channel, db)
+    }
+
+    /// Set the input PGA gain, from -12 dB to +35.25 dB in 0.75 dB steps.
+    ///
+    /// See [`Codec::set_volume_db`].
+    pub fn set_pga_gain_db(
+        &mut self,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        self.set_volume_db(VolumeStage::Pga, channel, db)
+    }
+
+    /// Set the headphone output volume, from -57 dB to +6 dB in 1 dB steps.
+    ///
+    /// See [`Codec::set_volume_db`].
+    pub fn set_headphone_volume_db(
+        &mut self,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        self.set_volume_db(VolumeStage::Headphone, channel, db)
+    }
+
+    /// Set the loudspeaker output volume, from -57 dB to +6 dB in 1 dB steps.
+    ///
+    /// See [`Codec::set_volume_db`].
+    pub fn set_speaker_volume_db(
+        &mut self,
+        channel: Channel,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        self.set_volume_db(VolumeStage::Speaker, channel, db)
+    }
+
+    /// Set the gain from a line or auxiliary input into the ADC boost stage,
+    /// from -12 dB to +6 dB in 3 dB steps.
+    ///
+    /// Give `f32::NEG_INFINITY` to disconnect the input. Returns the gain
+    /// actually applied, or [`Error::OutOfRange`].
+    pub fn set_boost_gain_db(
+        &mut self,
+        channel: Channel,
+        input: BoostInput,
+        db: f32,
+    ) -> Result<Decibels, Error<I::Error>> {
+        let range = input.range();
+        let code = range.code_for(db).ok_or(Error::OutOfRange)?;
+        let register = match channel {
+            Channel::Left => Register::LeftADCBoost,
+            Channel::Right => Register::RightADCBoost,
+        };
+        let shift = input.shift();
+        self.modify_register(register, |value| {
+            (value & !(0x7 << shift)) | (u16::from(code) << shift)
+        })?;
+        Ok(range.decibels(code))
+    }
+
+    /// Prepare to ramp both channels of a stage to a new gain.
+    ///
+    /// Reads the current gains, then returns a [`VolumeRamp`] which moves
+    /// them by roughly `step_db` each time it is ticked. Nothing is written
+    /// until the first tick. The step is rounded to a whole number of codes
+    /// (at least one), and must be a positive number.
+    pub fn start_volume_ramp(
+        &mut self,
+        stage: VolumeStage,
+        target_db: f32,
+        step_db: f32,
+    ) -> Result<VolumeRamp, Error<I::Error>> {
+        let range = stage.range();
+        let target = range.code_for(target_db).ok_or(Error::OutOfRange)?;
+        let steps = step_db / range.step_db;
+        if !(steps > 0.0 && steps <= f32::from(u8::MAX)) {
+            return Err(Error::OutOfRange);
+        }
+        let step = (steps + 0.5) as u8;
+        let mask = stage.gain_mask();
+        let left = self.read_register(stage.register(Channel::Left))? & mask;
+        let right = self.read_register(stage.register(Channel::Right))? & mask;
+        Ok(VolumeRamp::new(
+            stage,
+            left as u8,
+            right as u8,
+            target,
+            step,
+        ))
+    }
+
+    /// Ramp both channels of a stage to a new gain, waiting `step_delay_us`
+    /// microseconds between each step.
+    ///
+    /// Large jumps in gain are audible even with zero-cross detection on, so
+    /// this walks the gain code a step at a time. Returns the gain reached.
+    /// See [`Codec::start_volume_ramp`] for the rules on `step_db`, and
+    /// [`VolumeRamp`] for a version which doesn't block.
+    ///
+    /// A [`DelayNs`] only knows how to wait, not how long for, so the time
+    /// between steps is given separately. With zero-cross detection on, make
+    /// it longer than half a cycle of the lowest frequency being played, so
+    /// each step has a chance to take effect.
+    ///
+    /// ```
+    /// # use embedded_hal::i2c::{self as hali2c, ErrorKind, I2c, Operation, SevenBitAddress};
+    /// # #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    /// # pub enum Error { }
+    /// # impl hali2c::Error for Error {
+    /// #     fn kind(&self) -> ErrorKind {
+    /// #         ErrorKind::Other
+    /// #     }
+    /// # }
+    /// /// Stores every register, and records every register write
+    /// struct MockI2c {
+    ///     registers: [u16; 0x50],
+    ///     writes: Vec<(u8, u16)>,
+    /// }
+    /// # impl hali2c::ErrorType for MockI2c {
+    /// #     type Error = Error;
+    /// # }
+    /// impl I2c<SevenBitAddress> for MockI2c {
+    ///     fn transaction(&mut self, _address: u8, operations: &mut [Operation<'_>]) -> Result<(), Error> {
+    ///         match operations {
+    ///             [Operation::Write(data)] => {
+    ///                 let value = (u16::from(data[0] & 1) << 8) | u16::from(data[1]);
+    ///                 self.registers[usize::from(data[0] >> 1)] = value;
+    ///                 self.writes.push((data[0] >> 1, value));
+    ///             }
+    ///             [Operation::Write(data), Operation::Read(buffer)] => {
+    ///                 let value = self.registers[usize::from(data[0] >> 1)];
+    ///                 buffer.copy_from_slice(&value.to_be_bytes());
+    ///             }
+    ///             _ => unimplemented!(),
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    /// /// Adds up how long we have been asked to wait
+    /// #[derive(Default)]
+    /// struct MockDelay {
+    ///     total_ns: u64,
+    /// }
+    /// impl embedded_hal::delay::DelayNs for MockDelay {
+    ///     fn delay_ns(&mut self, ns: u32) {
+    ///         self.total_ns += u64::from(ns);
+    ///     }
+    /// }
+    ///
+    /// use nau88c22::volume::VolumeStage;
+    /// let mut i2c = MockI2c { registers: [0; 0x50], writes: Vec::new() };
+    /// // Both headphone channels at -10 dB
+    /// i2c.registers[0x34] = 0x02F;
+    /// i2c.registers[0x35] = 0x02F;
+    /// let mut codec = nau88c22::Codec::new(i2c);
+    /// let mut delay = MockDelay::default();
+    /// // Bring the headphones up to 0 dB, 4 dB every 5 ms
+    /// let reached = codec
+    ///     .ramp_volume(VolumeStage::Headphone, 0.0, 4.0, 5_000, &mut delay)
+    ///     .unwrap();
+    /// assert_eq!(reached.db(), 0.0);
+    /// // Three steps (-6 dB, -2 dB and 0 dB), with a wait between each
+    /// assert_eq!(delay.total_ns, 10_000_000);
+    /// let i2c = codec.release();
+    /// assert_eq!(
+    ///     i2c.writes,
+    ///     [(0x34, 0x033), (0x35, 0x133), (0x34, 0x037), (0x35, 0x137), (0x34, 0x039), (0x35, 0x139)]
+    /// );
+    /// ```
+    pub fn ramp_volume<D>(
+        &mut self,
+        stage: VolumeStage,
+        target_db: f32,
+        step_db: f32,
+        step_delay_us: u32,
+        delay: &mut D,
+    ) -> Result<Decibels, Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        let mut ramp = self.start_volume_ramp(stage, target_db, step_db)?;
+        while !ramp.tick(self)? {
+            delay.delay_us(step_delay_us);
+        }
+        Ok(ramp.target())
+    }
+}
+
+// End of file